@@ -5,20 +5,16 @@ use dialog::backends;
 use dialog::DialogBox;
 
 fn main() -> dialog::Result<()> {
-    let mut backend = backends::Dialog::new();
-
+    let backend = backends::Dialog::new()
+        .set_title("And this is a title:");
     dialog::Message::new("This is a message.")
-        .title("And this is a title:")
         .show_with(&backend)?;
 
-    backend.set_backtitle("Backtitle");
+    let backend = backend.set_backtitle("Backtitle");
     dialog::Message::new("This is a message.")
-        .title("And this is a title:")
         .show_with(&backend)?;
 
-    backend.set_width(100);
-    backend.set_height(10);
+    let backend = backend.set_width(100).set_height(10);
     dialog::Message::new("This is a message with a fixed size.")
-        .title("And this is a title:")
         .show_with(&backend)
 }
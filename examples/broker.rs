@@ -0,0 +1,15 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use dialog::backends;
+
+fn main() -> dialog::Result<()> {
+    let broker = backends::BrokerServer::bind(backends::default_broker_path())?;
+    println!("Waiting for dialog boxes on {}", broker.path().display());
+    let backend = dialog::default_backend()?;
+    loop {
+        if let Err(err) = broker.serve_one(backend.as_ref()) {
+            eprintln!("Could not serve dialog boxes: {}", err);
+        }
+    }
+}
@@ -1,20 +1,23 @@
 // Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
 // SPDX-License-Identifier: MIT
 
+use dialog::backends;
 use dialog::DialogBox;
 
 fn main() -> dialog::Result<()> {
+    let backend = backends::Dialog::new()
+        .set_title("File Chooser Example (Open)");
     let choice = dialog::FileSelection::new("Please select a file")
-        .title("File Chooser Example (Open)")
         .path("/etc")
-        .show()?;
+        .show_with(&backend)?;
     println!("The user chose: {:?}", choice);
 
+    let backend = backends::Dialog::new()
+        .set_title("File Chooser Example (Save)");
     let choice = dialog::FileSelection::new("Please select a file")
-        .title("File Chooser Example (Save)")
         .mode(dialog::FileSelectionMode::Save)
         .path("/etc")
-        .show()?;
+        .show_with(&backend)?;
     println!("The user chose: {:?}", choice);
 
     Ok(())
@@ -0,0 +1,58 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! A reference frontend for the `Frontend` backend.
+//!
+//! This frontend reads requests from the standard input and answers every dialog box with its
+//! default value: the first menu item, the preselected checklist items, the default input, the
+//! initial form values and the yes button.  Passwords are answered with an empty string.
+
+use std::io::{self, BufRead, Write};
+
+use dialog::backends::Reply;
+use dialog::{Choice, Request, Response};
+
+fn answer(request: Request) -> Response {
+    let value = match request {
+        Request::Checklist { list, .. } => Some(
+            list.into_iter()
+                .filter(|(_, _, status)| *status)
+                .map(|(tag, _, _)| tag)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Request::FileSelection { path, .. } => path.map(|p| p.to_string_lossy().into_owned()),
+        Request::Form { list, .. } | Request::MixedForm { list, .. } => Some(
+            list.into_iter()
+                .map(|(_, _, _, item, _, _, _, _)| item)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Request::PasswordForm { list, .. } => Some(vec![""; list.len()].join("\n")),
+        Request::Input { default, .. } => Some(default.unwrap_or_default()),
+        Request::Menu { list, .. } => list.into_iter().next().map(|[tag, _]| tag),
+        Request::Password { .. } => Some(String::new()),
+        Request::Gauge { .. }
+        | Request::Message { .. }
+        | Request::MixedGauge { .. }
+        | Request::Question { .. } => None,
+    };
+    Response::new(Choice::Yes, value)
+}
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let reply = match serde_json::from_str::<Request>(&line?) {
+            Ok(request) => Reply::Response(answer(request)),
+            Err(err) => Reply::Error {
+                error: err.to_string(),
+            },
+        };
+        serde_json::to_writer(&mut stdout, &reply)?;
+        writeln!(stdout)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
@@ -1,17 +1,18 @@
 // Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
 // SPDX-License-Identifier: MIT
 
+use dialog::backends;
 use dialog::DialogBox;
 
 fn main() -> dialog::Result<()> {
     let input1 = dialog::Input::new("Please enter something").show()?;
+    let backend = backends::Dialog::new()
+        .set_title("Input form");
     let input2 = dialog::Input::new("Please enter something")
-        .title("Input form")
-        .show()?;
+        .show_with(&backend)?;
     let input3 = dialog::Input::new("Please enter something with a default")
-        .title("Input form")
         .default("input")
-        .show()?;
+        .show_with(&backend)?;
 
     println!("Input 1: {:?}", input1);
     println!("Input 2: {:?}", input2);
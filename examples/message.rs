@@ -1,12 +1,14 @@
 // Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
 // SPDX-License-Identifier: MIT
 
+use dialog::backends;
 use dialog::DialogBox;
 
 fn main() -> dialog::Result<()> {
     dialog::Message::new("This is a message.").show()?;
 
+    let backend = backends::Dialog::new()
+        .set_title("And this is a title:");
     dialog::Message::new("This is a message.")
-        .title("And this is a title:")
-        .show()
+        .show_with(&backend)
 }
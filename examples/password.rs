@@ -1,12 +1,14 @@
 // Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
 // SPDX-License-Identifier: MIT
 
+use dialog::backends;
 use dialog::DialogBox;
 
 fn main() -> dialog::Result<()> {
-    let password = dialog::Password::new("Please enter a new password")
-        .title("Password")
-        .show()?;
+    let backend = backends::Dialog::new()
+        .set_title("Password");
+    let (_, password) = dialog::Password::new("Please enter a new password")
+        .show_with(&backend)?;
     match password {
         Some(password) => println!("Your new password is: {}", password),
         None => println!("You do not want to have a password."),
@@ -0,0 +1,86 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use crate::backends::{AsyncBackend, BoxFuture};
+use crate::request::{checklist_output, choice_output, choices_output, unit_output};
+use crate::{
+    Checklist, DialogBox,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Result
+};
+
+/// A dialog box that can be shown asynchronously using an [`AsyncBackend`][].
+///
+/// This trait is only available if the `tokio` feature is enabled.  The returned future does not
+/// block the executor while the dialog box is displayed.  If the future is dropped before it
+/// completes, the dialog box is closed.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() -> dialog::Result<()> {
+/// use dialog::AsyncDialogBox;
+///
+/// let backend = dialog::backends::Dialog::new();
+/// let (_, name) = dialog::Input::new("Please enter your name")
+///     .show_with_async(&backend)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`AsyncBackend`]: backends/trait.AsyncBackend.html
+pub trait AsyncDialogBox: DialogBox {
+    /// Shows this dialog box using the given backend and returns the output.
+    fn show_with_async<'a, B>(&self, backend: &'a B) -> BoxFuture<'a, Result<Self::Output>>
+    where
+        B: AsyncBackend + ?Sized;
+}
+
+// Implements AsyncDialogBox for a dialog box type using the given function to convert the
+// response into the output of the dialog box.
+macro_rules! impl_async_dialog_box {
+    ($($type:ty => $output:expr,)*) => {
+        $(
+            impl AsyncDialogBox for $type {
+                fn show_with_async<'a, B>(&self, backend: &'a B)
+                    -> BoxFuture<'a, Result<Self::Output>>
+                where
+                    B: AsyncBackend + ?Sized,
+                {
+                    let response = backend.show_request(self.into());
+                    Box::pin(async move { response.await.map($output) })
+                }
+            }
+        )*
+    };
+}
+
+// File selections are shown with a separate method so that backends can return paths that are not
+// valid UTF-8.
+impl AsyncDialogBox for FileSelection {
+    fn show_with_async<'a, B>(&self, backend: &'a B) -> BoxFuture<'a, Result<Self::Output>>
+    where
+        B: AsyncBackend + ?Sized,
+    {
+        backend.show_file_selection(self)
+    }
+}
+
+impl_async_dialog_box! {
+    Checklist => checklist_output,
+    Form => choices_output,
+    Gauge => unit_output,
+    Input => choices_output,
+    Menu => choices_output,
+    Message => unit_output,
+    MixedForm => choices_output,
+    MixedGauge => unit_output,
+    Password => choices_output,
+    PasswordForm => choices_output,
+    Question => choice_output,
+}
@@ -0,0 +1,420 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::env;
+use std::fs;
+use std::io;
+use std::mem;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::backends::frontend::{send_request, serve_requests};
+use crate::backends::{Backend, Capabilities};
+use crate::{
+    Checklist, Choice, Error,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Request, Response, Result
+};
+
+// The time that the broker waits for a request from a client.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// A counter for the names of the temporary directories used by BrokerServer::bind.
+static BIND_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the default path of the broker socket.
+///
+/// The path is read from the `DIALOG_BROKER` environment variable.  If it is not set, the socket
+/// `dialog-broker.sock` in the directory `XDG_RUNTIME_DIR` is used.  If that is not set either,
+/// the socket `broker.sock` in the directory `dialog-<uid>` in the temporary directory is used.
+/// [`BrokerServer::bind`][] creates this directory so that only the current user can access it.
+///
+/// [`BrokerServer::bind`]: struct.BrokerServer.html#method.bind
+pub fn default_broker_path() -> PathBuf {
+    if let Some(path) = env::var_os("DIALOG_BROKER") {
+        return PathBuf::from(path);
+    }
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("dialog-broker.sock"),
+        None => private_dir().join("broker.sock"),
+    }
+}
+
+// Returns the per-user directory for the broker socket if XDG_RUNTIME_DIR is not set.
+fn private_dir() -> PathBuf {
+    env::temp_dir().join(format!("dialog-{}", current_uid()))
+}
+
+// Creates the per-user directory, or checks that an existing directory belongs to the current
+// user and cannot be accessed by other users.
+fn create_private_dir(dir: &Path) -> Result<()> {
+    match fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => return Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(err) => return Err(err.into()),
+    }
+    let metadata = fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != current_uid() || metadata.mode() & 0o077 != 0 {
+        return Err(Error::Error(format!("{} is not a private directory of the current user",
+            dir.display())));
+    }
+    Ok(())
+}
+
+fn current_uid() -> libc::uid_t {
+    unsafe { libc::geteuid() }
+}
+
+// Returns the user and group ID of the process on the other end of the socket.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_cred(stream: &UnixStream) -> io::Result<(libc::uid_t, libc::gid_t)> {
+    let mut cred: libc::ucred = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if ret == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((cred.uid, cred.gid))
+}
+
+// Returns the user and group ID of the process on the other end of the socket.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn peer_cred(stream: &UnixStream) -> io::Result<(libc::uid_t, libc::gid_t)> {
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((uid, gid))
+}
+
+// Checks that the process on the other end of the socket runs as the current user, as root, as
+// one of the allowed users or with the allowed group.
+fn check_peer(stream: &UnixStream, users: &[u32], group: Option<u32>) -> Result<()> {
+    let (uid, gid) = peer_cred(stream)?;
+    if uid != current_uid() && uid != 0 && !users.contains(&uid) && group != Some(gid) {
+        return Err(Error::Error(format!("refusing to talk to a process of user {}", uid)));
+    }
+    Ok(())
+}
+
+/// The `broker` backend.
+///
+/// This backend forwards all dialog boxes to a [`BrokerServer`][] over a Unix socket, using the
+/// JSON lines protocol of the [`Frontend`][] backend.  The broker displays the dialog boxes on
+/// its own terminal and returns the answers.  This allows processes without a terminal, for
+/// example background services, to ask an operator.
+///
+/// A new connection is opened for each dialog box, so the broker can be started after the
+/// backend was created.  The backend only talks to brokers that run as the current user or as
+/// root.  If the broker runs as another user, for example the operator of a background service,
+/// this user has to be allowed with [`allow_user`][].
+///
+/// # Example
+///
+/// ```no_run
+/// use dialog::DialogBox;
+///
+/// let backend = dialog::backends::Broker::new("/run/user/1000/dialog-broker.sock");
+/// let choice = dialog::Question::new("Restart the database now?")
+///     .show_with(&backend)
+///     .expect("Could not display dialog box");
+/// ```
+///
+/// [`allow_user`]: #method.allow_user
+/// [`BrokerServer`]: struct.BrokerServer.html
+/// [`Frontend`]: struct.Frontend.html
+#[derive(Debug)]
+pub struct Broker {
+    path: PathBuf,
+    users: Vec<u32>,
+}
+
+impl Broker {
+    /// Creates a new `Broker` instance that connects to the broker socket at the given path.
+    pub fn new(path: impl AsRef<Path>) -> Broker {
+        Broker {
+            path: path.as_ref().to_path_buf(),
+            users: Vec::new(),
+        }
+    }
+
+    /// Also talks to brokers that run as the user with the given ID.
+    pub fn allow_user(mut self, uid: u32) -> Broker {
+        self.users.push(uid);
+        self
+    }
+
+    fn show(&self, request: Request) -> Result<Response> {
+        let stream = UnixStream::connect(&self.path)?;
+        check_peer(&stream, &self.users, None)?;
+        let mut reader = io::BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        send_request(&mut writer, &mut reader, &request)
+    }
+
+    fn show_choices(&self, request: Request) -> Result<(Choice, Option<String>)> {
+        self.show(request).map(|response| (response.choice, response.value))
+    }
+}
+
+impl Default for Broker {
+    fn default() -> Self {
+        Broker::new(default_broker_path())
+    }
+}
+
+impl AsRef<Broker> for Broker {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl Backend for Broker {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::all_boxes()
+    }
+
+    fn can_run(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let (choice, value) = self.show_choices(checklist.into())?;
+        let tags = value.map(|value| value.lines().map(ToString::to_string).collect());
+        Ok((choice, tags))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.show_choices(file_selection.into())
+            .map(|(choice, path)| (choice, path.map(PathBuf::from)))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.show(gauge.into()).map(|_| ())
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.show_choices(input.into())
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        self.show_choices(menu.into())
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.show(message.into()).map(|_| ())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.show(gauge.into()).map(|_| ())
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.show_choices(password.into())
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.show(question.into()).map(|response| response.choice)
+    }
+}
+
+/// A prompt broker that displays dialog boxes received from [`Broker`][] backends.
+///
+/// The broker listens on a Unix socket and displays the received dialog boxes one after another
+/// using a backend of its own, typically on the terminal of an operator.  By default, the socket
+/// is only accessible by its owner, and the broker only accepts connections from processes that
+/// run as the same user or as root.  Clients that run as other users, for example background
+/// services, have to be allowed with [`set_allowed_users`][] or [`set_allowed_group`][].  The
+/// socket file is removed when the broker is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// let broker = dialog::backends::BrokerServer::bind(dialog::backends::default_broker_path())
+///     .expect("Could not start the broker");
+/// let backend = dialog::backends::Dialog::new();
+/// loop {
+///     if let Err(err) = broker.serve_one(&backend) {
+///         eprintln!("Could not serve dialog boxes: {}", err);
+///     }
+/// }
+/// ```
+///
+/// [`Broker`]: struct.Broker.html
+/// [`set_allowed_group`]: #method.set_allowed_group
+/// [`set_allowed_users`]: #method.set_allowed_users
+#[derive(Debug)]
+pub struct BrokerServer {
+    path: PathBuf,
+    // The device and inode of the socket file, used to check that it has not been replaced.
+    file_id: (u64, u64),
+    listener: UnixListener,
+    users: Vec<u32>,
+    group: Option<u32>,
+}
+
+impl BrokerServer {
+    /// Creates a broker listening on the Unix socket at the given path.
+    ///
+    /// If a stale socket of the current user exists at the path, it is replaced.  If another
+    /// broker is already listening on the path, or if the path is used by another file, an error
+    /// is returned.
+    pub fn bind(path: impl AsRef<Path>) -> Result<BrokerServer> {
+        let path = path.as_ref().to_path_buf();
+        let dir = private_dir();
+        if path.parent() == Some(dir.as_path()) {
+            create_private_dir(&dir)?;
+        }
+        if let Ok(metadata) = fs::symlink_metadata(&path) {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(Error::Error(format!("a broker is already listening on {}",
+                    path.display())));
+            }
+            if !metadata.file_type().is_socket() || metadata.uid() != current_uid() {
+                return Err(Error::Error(format!("{} exists and is not a broker socket",
+                    path.display())));
+            }
+            fs::remove_file(&path)?;
+        }
+
+        // Create the socket in a private temporary directory and restrict its permissions before
+        // moving it to its path, so that other users cannot connect in between.
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let tmp_dir = parent.join(format!(".dialog-broker-{}-{}", std::process::id(),
+            BIND_COUNTER.fetch_add(1, Ordering::SeqCst)));
+        fs::DirBuilder::new().mode(0o700).create(&tmp_dir)?;
+        let tmp_path = tmp_dir.join("broker.sock");
+        let listener = UnixListener::bind(&tmp_path).and_then(|listener| {
+            fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o600))?;
+            fs::rename(&tmp_path, &path)?;
+            Ok(listener)
+        });
+        let _ = fs::remove_file(&tmp_path);
+        let _ = fs::remove_dir(&tmp_dir);
+        let listener = listener?;
+        let metadata = fs::symlink_metadata(&path)?;
+        Ok(BrokerServer {
+            path,
+            file_id: (metadata.dev(), metadata.ino()),
+            listener,
+            users: Vec::new(),
+            group: None,
+        })
+    }
+
+    /// Also accepts connections from processes that run as one of the given users.
+    ///
+    /// The socket is made accessible by all users, and the user ID of each client is checked
+    /// when it connects.  The directory of the socket must be accessible by these users, so the
+    /// default path in the private directory of the current user cannot be used.
+    pub fn set_allowed_users(mut self, uids: impl IntoIterator<Item = u32>) -> Result<BrokerServer> {
+        self.users = uids.into_iter().collect();
+        self.set_mode()?;
+        Ok(self)
+    }
+
+    /// Also accepts connections from processes that run with the given group.
+    ///
+    /// The socket is made accessible by the members of the group.  When a client connects, its
+    /// effective group ID is compared with the given group, so supplementary groups of the client
+    /// are not taken into account.  The directory of the socket must be accessible by the group,
+    /// so the default path in the private directory of the current user cannot be used.
+    pub fn set_allowed_group(mut self, gid: u32) -> Result<BrokerServer> {
+        let path = CString::new(self.path.as_os_str().as_bytes())
+            .map_err(|_| Error::InvalidConfig("socket path contains a null byte".to_string()))?;
+        if unsafe { libc::chown(path.as_ptr(), libc::uid_t::MAX, gid) } == -1 {
+            return Err(io::Error::last_os_error().into());
+        }
+        self.group = Some(gid);
+        self.set_mode()?;
+        Ok(self)
+    }
+
+    // Sets the permissions of the socket according to the allowed users and group.
+    fn set_mode(&self) -> Result<()> {
+        let mode = if !self.users.is_empty() {
+            0o666
+        } else if self.group.is_some() {
+            0o660
+        } else {
+            0o600
+        };
+        fs::set_permissions(&self.path, fs::Permissions::from_mode(mode))?;
+        Ok(())
+    }
+
+    /// Returns the path of the socket.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Accepts a connection from a client and displays its dialog boxes using the given backend.
+    ///
+    /// This method returns when the client closes the connection.  If accepting the connection
+    /// or displaying the dialog boxes fails, or if the client does not send a request within ten
+    /// seconds, an error is returned.
+    pub fn serve_one(&self, backend: &dyn Backend) -> Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        check_peer(&stream, &self.users, self.group)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let reader = io::BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        serve_requests(reader, &mut writer, backend)
+    }
+
+    /// Displays the dialog boxes received from clients using the given backend.
+    ///
+    /// The clients are served one after another until an error occurs, see [`serve_one`][].
+    ///
+    /// [`serve_one`]: #method.serve_one
+    pub fn serve(&self, backend: &dyn Backend) -> Result<()> {
+        loop {
+            self.serve_one(backend)?;
+        }
+    }
+}
+
+impl Drop for BrokerServer {
+    fn drop(&mut self) {
+        // Only remove the socket if it has not been replaced by another process
+        if let Ok(metadata) = fs::symlink_metadata(&self.path) {
+            if (metadata.dev(), metadata.ino()) == self.file_id {
+                let _ = fs::remove_file(&self.path);
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use crate::BoxKind;
+
+/// All dialog box types.
+const ALL_BOXES: &[BoxKind] = &[
+    BoxKind::Checklist,
+    BoxKind::FileSelection,
+    BoxKind::Form,
+    BoxKind::Gauge,
+    BoxKind::Input,
+    BoxKind::Menu,
+    BoxKind::Message,
+    BoxKind::MixedForm,
+    BoxKind::MixedGauge,
+    BoxKind::Password,
+    BoxKind::PasswordForm,
+    BoxKind::Question,
+];
+
+/// The features supported by a backend.
+///
+/// Use [`Backend::capabilities`][] to query the capabilities of a backend before showing a dialog
+/// box, for example to choose a different dialog box type if a type is not supported.  Backends
+/// return an [`Error::Unsupported`][] error for dialog box types they do not support, and ignore
+/// optional features they do not support.
+///
+/// # Example
+///
+/// ```
+/// use dialog::backends::{Backend, Rofi};
+/// use dialog::BoxKind;
+///
+/// let capabilities = Rofi::new().capabilities();
+/// assert!(capabilities.supports(BoxKind::Menu));
+/// assert!(!capabilities.supports(BoxKind::Form));
+/// ```
+///
+/// [`Backend::capabilities`]: trait.Backend.html#method.capabilities
+/// [`Error::Unsupported`]: ../enum.Error.html#variant.Unsupported
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The dialog box types supported by the backend.
+    pub boxes: Vec<BoxKind>,
+    /// Whether the backend honours [`FileSelectionMode::Save`][] and allows selecting files that
+    /// do not exist yet.
+    ///
+    /// [`FileSelectionMode::Save`]: ../enum.FileSelectionMode.html#variant.Save
+    pub save_mode: bool,
+    /// Whether the backend can display an extra button.
+    pub extra_button: bool,
+    /// Whether the backend can display a help button.
+    pub help_button: bool,
+    /// Whether the backend can display colored text.
+    pub colors: bool,
+    /// Whether the backend can close dialog boxes after a timeout.
+    pub timeout: bool,
+}
+
+impl Capabilities {
+    /// Returns capabilities with support for all dialog box types and no optional features.
+    pub fn all_boxes() -> Capabilities {
+        Capabilities::with_boxes(ALL_BOXES)
+    }
+
+    /// Returns capabilities with support for the given dialog box types and no optional features.
+    pub fn with_boxes(boxes: &[BoxKind]) -> Capabilities {
+        Capabilities {
+            boxes: boxes.to_vec(),
+            save_mode: false,
+            extra_button: false,
+            help_button: false,
+            colors: false,
+            timeout: false,
+        }
+    }
+
+    /// Checks whether the given dialog box type is supported.
+    pub fn supports(&self, kind: BoxKind) -> bool {
+        self.boxes.contains(&kind)
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::all_boxes()
+    }
+}
@@ -1,22 +1,61 @@
 // Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
 // SPDX-License-Identifier: MIT
 
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::OnceLock;
+use std::thread;
+#[cfg(feature = "tokio")]
+use std::time::{Duration, Instant};
 
+use crate::backends::running::Terminal;
+use crate::backends::{Capabilities, Running, Script};
+use crate::request::{self, RawResponse};
 use crate::{
-    Choice, Error, 
+    BoxKind, Checklist, Choice, Error, 
     FileSelection, Form,
     Gauge, Menu, MixedForm, 
     MixedGauge, Input, 
     Message, Password,
-    PasswordForm,
-    Question, Result
+    FormField, PasswordForm,
+    Question, Request, Response, Result
 };
 
 /// The `dialog` backend.
 ///
 /// This backend uses the external `dialog` program (not to be confused with this crate also called
 /// `dialog`) to display text-based dialog boxes in the terminal.
+///
+/// If the standard input or output of the process is redirected, for example to a pipe or a file,
+/// the dialog boxes are displayed on the controlling terminal `/dev/tty` instead.  If there is no
+/// controlling terminal, an [`Error::NoTerminal`][] error is returned.
+///
+/// The text of the dialog boxes is never interpreted as an option by `dialog`, even if it starts
+/// with `--`.  Control characters except newlines and tabs are removed from the text.
+///
+/// Default values and the items of forms are not passed on the command line, where other users
+/// could read them, but through a pipe using the `--file` option of `dialog`.  The same applies to
+/// argument lists that would exceed the limits of the operating system, for example long menus.
+///
+/// Before the first dialog box is displayed, the version of `dialog` is queried, see
+/// [`version`][].  If the installed version does not support a dialog box, an
+/// [`Error::Unsupported`][] error is returned instead of running `dialog`.  Versions without the
+/// `--file` option get long argument lists on the command line, and dialog boxes with default
+/// values or form items are rejected.
+///
+/// The backend can also use `whiptail` instead of `dialog`, see [`whiptail`][].
+///
+/// [`Error::NoTerminal`]: ../enum.Error.html#variant.NoTerminal
+/// [`Error::Unsupported`]: ../enum.Error.html#variant.Unsupported
+/// [`version`]: #method.version
+/// [`whiptail`]: #method.whiptail
 #[derive(Debug)]
 pub struct Dialog {
     backtitle: Option<String>,
@@ -26,7 +65,16 @@ pub struct Dialog {
     label_cancelbutton: Option<String>,
     label_helpbutton: Option<String>,
     insecure: bool,
+    colors: bool,
     cancelbutton: bool,
+    timeout: Option<u32>,
+    timeout_answer: Option<Choice>,
+    whiptail: bool,
+    executable: String,
+    wrapper: Vec<String>,
+    env: Vec<(String, String)>,
+    current_dir: Option<PathBuf>,
+    version: OnceLock<Option<String>>,
     width: String,
     height: String,
 }
@@ -37,6 +85,35 @@ impl Dialog {
         Default::default()
     }
 
+    /// Creates a new `Dialog` instance that uses `whiptail` instead of `dialog`.
+    ///
+    /// `whiptail` supports checklists, gauges, inputs, menus, messages, passwords and questions.
+    /// Other dialog boxes return an [`Error::Unsupported`][] error.  `whiptail` has no `--file`
+    /// option, so inputs with a default value are rejected too, as the default value would be
+    /// visible in the process list.  The extra and help buttons, colors, timeouts and the
+    /// insecure password mode are not supported and ignored.  The executable can be changed with
+    /// [`set_executable`][].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let whiptail = dialog::backends::Dialog::whiptail().set_oklabel("Next");
+    /// let command = whiptail.command_line(&dialog::Message::new("--help is not an option"))
+    ///     .expect("Could not build command line");
+    /// assert_eq!(command.to_string(),
+    ///     "whiptail --output-fd 3 --ok-button Next --msgbox -- '--help is not an option' 0 0");
+    /// ```
+    ///
+    /// [`Error::Unsupported`]: ../enum.Error.html#variant.Unsupported
+    /// [`set_executable`]: #method.set_executable
+    pub fn whiptail() -> Dialog {
+        Dialog {
+            whiptail: true,
+            executable: "whiptail".to_string(),
+            ..Default::default()
+        }
+    }
+
     /// Sets the backtitle for the dialog box.
     ///
     /// The backtitle is displayed on the backdrop, at the top of the screen.
@@ -103,6 +180,61 @@ impl Dialog {
         self
     }
 
+    /// Enables the interpretation of color escape sequences in the text of the dialog box.
+    ///
+    /// The sequences start with `\Z`, for example `\Z1` for red text and `\Zn` to restore the
+    /// normal settings.  See the dialog manual for the list of sequences.
+    pub fn set_colors(mut self, colors: bool) -> Dialog {
+        self.colors = colors;
+        self
+    }
+
+    /// Sets the timeout for all dialog boxes in seconds.
+    ///
+    /// If the user does not answer a dialog box within the timeout, `dialog` closes it and
+    /// [`Choice::Timeout`][] is returned.  Messages and gauges are closed without an error.  The
+    /// timeout of a dialog box, for example [`Input::timeout`][], overrides this setting.  Older
+    /// versions of `dialog` report a timeout like the ESC key, so [`Choice::Escape`][] is returned
+    /// instead.  Not all backends support the timeout of a dialog box, see
+    /// [`Capabilities::timeout`][].
+    ///
+    /// [`Capabilities::timeout`]: struct.Capabilities.html#structfield.timeout
+    /// [`Choice::Timeout`]: ../enum.Choice.html#variant.Timeout
+    /// [`Choice::Escape`]: ../enum.Choice.html#variant.Escape
+    /// [`Input::timeout`]: ../struct.Input.html#method.timeout
+    pub fn set_timeout(mut self, seconds: u32) -> Dialog {
+        self.timeout = Some(seconds);
+        self
+    }
+
+    /// Sets the answer that is returned if a dialog box times out.
+    ///
+    /// By default, [`Choice::Timeout`][] is returned without a value.  If this option is set, a
+    /// timeout is treated as if the user had pressed the given button without changing the
+    /// dialog box:  the value is the default of an input box, the initially selected tags of a
+    /// checklist, the first tag of a menu, the initial directory of a file selection and the
+    /// initial items of a form.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dialog::{Choice, DialogBox};
+    ///
+    /// let backend = dialog::backends::Dialog::new()
+    ///     .set_timeout(60)
+    ///     .set_timeout_answer(Choice::Yes);
+    /// let (_, name) = dialog::Input::new("Please enter the host name")
+    ///     .default("localhost")
+    ///     .show_with(&backend)
+    ///     .expect("Could not display dialog box");
+    /// ```
+    ///
+    /// [`Choice::Timeout`]: ../enum.Choice.html#variant.Timeout
+    pub fn set_timeout_answer(mut self, choice: Choice) -> Dialog {
+        self.timeout_answer = Some(choice);
+        self
+    }
+
     /// Sets the height of the dialog box.
     ///
     /// The height is given in characters.  The actual height of the dialog box might be higher
@@ -121,70 +253,514 @@ impl Dialog {
         self
     }
 
-    fn execute(&self, boxtype: &str, boxtype_arg: &Option<String>,
-        args: Vec<&str>, echo_child: Option<process::Child>) -> Result<process::Output> {
-        let mut command = process::Command::new("dialog");
+    /// Sets the `dialog` executable.
+    ///
+    /// The executable can be a program name that is looked up in the `PATH` or a path, for
+    /// example to use a vendored build of `dialog`.  The default executable is `dialog`.
+    pub fn set_executable(mut self, executable: impl Into<String>) -> Dialog {
+        self.executable = executable.into();
+        self
+    }
 
-        match echo_child {
-            Some(echo_child) => command.stdin(process::Stdio::from(echo_child.stdout.unwrap())),
-            None => command.stdin(process::Stdio::inherit()),
-        };
+    /// Sets a command that is used to run `dialog`.
+    ///
+    /// The executable and its arguments are appended to the wrapper command, for example
+    /// `flatpak-spawn --host` or `env LANG=C`.  The wrapper has to pass on the file descriptors 3
+    /// and 4 to `dialog`, which are used for the answer and for the options.  For
+    /// `flatpak-spawn`, the backend adds the `--forward-fd` options for these file descriptors
+    /// and passes the environment variables set with [`set_env`][] as `--env` options.  `sudo`
+    /// and `doas` close the file descriptors, so displaying a dialog box with one of these
+    /// wrappers returns an [`Error::InvalidConfig`][] error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let dialog = dialog::backends::Dialog::new()
+    ///     .set_wrapper(["flatpak-spawn", "--host"])
+    ///     .set_env("LANG", "C");
+    /// let command = dialog.command_line(&dialog::Message::new("Hello world!"))
+    ///     .expect("Could not build command line");
+    /// assert_eq!(command.program(), "flatpak-spawn");
+    /// assert_eq!(command.args()[..4], ["--host", "--forward-fd=3", "--env=LANG=C", "dialog"]);
+    /// assert!(command.env().is_empty());
+    /// ```
+    ///
+    /// [`set_env`]: #method.set_env
+    /// [`Error::InvalidConfig`]: ../enum.Error.html#variant.InvalidConfig
+    pub fn set_wrapper<I, S>(mut self, wrapper: I) -> Dialog
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.wrapper = wrapper.into_iter().map(Into::into).collect();
+        self
+    }
 
-        command.stdout(process::Stdio::inherit());
-        let mut common_options: Vec<&str> = Vec::new();
+    /// Sets an environment variable for `dialog`.
+    ///
+    /// The other environment variables are inherited from the current process.  If a wrapper is
+    /// set, the variable is set for the wrapper command, except for `flatpak-spawn`, see
+    /// [`set_wrapper`](#method.set_wrapper).
+    pub fn set_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Dialog {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets the working directory for `dialog`.
+    ///
+    /// Per default, the working directory of the current process is used.  Relative paths of file
+    /// selections are resolved in this directory.
+    pub fn set_current_dir(mut self, dir: impl Into<PathBuf>) -> Dialog {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Returns the version of `dialog`, for example `1.3-20201126`.
+    ///
+    /// The version is queried with `dialog --print-version` when it is needed for the first time
+    /// and then cached.  If the output of `dialog` does not contain a version, `None` is returned.
+    /// `whiptail` cannot print its version, so `None` is always returned for `whiptail`.
+    pub fn version(&self) -> Result<Option<String>> {
+        if self.whiptail {
+            return Ok(None);
+        }
+        if let Some(version) = self.version.get() {
+            return Ok(version.clone());
+        }
+        let mut command = self.base_command(&[])?;
+        command.arg("--print-version");
+        command.stdin(process::Stdio::null());
+        let output = command.output().map_err(|err| Error::from((self.program(), err)))?;
+        // Depending on the version, dialog prints the version to stdout or stderr.
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        let version = text
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Version:"))
+            .map(|version| version.trim().to_string());
+        Ok(self.version.get_or_init(|| version).clone())
+    }
+
+    /// Returns the command line that is used to display the given dialog box.
+    ///
+    /// The dialog box is not displayed.  This can be used to debug the layout of a dialog box or
+    /// to check the options passed to `dialog`.  Note that gauges also read the percentage from
+    /// the standard input, and that `dialog` writes the answer to the file descriptor 3.  The
+    /// command line contains all arguments, even if some of them are passed through a pipe when
+    /// the dialog box is displayed.  The command line includes the wrapper and the environment
+    /// variables, but not the working directory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let dialog = dialog::backends::Dialog::new().set_title("Greeting");
+    /// let command = dialog.command_line(&dialog::Message::new("Hello world!"))
+    ///     .expect("Could not build command line");
+    /// assert_eq!(command.args(), [
+    ///     "--output-fd", "3", "--title", "Greeting", "--msgbox", "Hello world!", "0", "0",
+    /// ]);
+    /// assert_eq!(
+    ///     command.to_string(),
+    ///     "dialog --output-fd 3 --title Greeting --msgbox 'Hello world!' 0 0",
+    /// );
+    /// ```
+    pub fn command_line(&self, request: impl Into<Request>) -> Result<CommandLine> {
+        self.wrap(self.args(&request.into())?, &[OUTPUT_FD])
+    }
+
+    /// Creates a shell script that displays a sequence of dialog boxes with the settings of this
+    /// backend.
+    ///
+    /// See [`Script`][] for more information.
+    ///
+    /// [`Script`]: struct.Script.html
+    pub fn script(&self) -> Script<'_> {
+        Script::new(self)
+    }
+
+    // Returns the name of the program used to display the dialog boxes.
+    fn name(&self) -> &'static str {
+        if self.whiptail { "whiptail" } else { "dialog" }
+    }
+
+    // Builds the arguments for the given dialog box, including the common options.  whiptail
+    // does not support some of the options, and others have different names.
+    fn args(&self, request: &Request) -> Result<Vec<OsString>> {
+        let whiptail = self.whiptail;
+        let mut args: Vec<OsString> = vec!["--output-fd".into(), OUTPUT_FD.to_string().into()];
 
         if let Some(ref backtitle) = self.backtitle {
-            common_options.push("--backtitle");
-            common_options.push(backtitle);
+            args.push("--backtitle".into());
+            push_text(&mut args, backtitle);
         }
 
         if let Some(ref title) = self.title {
-            common_options.push("--title");
-            common_options.push(title);
-        } 
+            args.push("--title".into());
+            push_text(&mut args, title);
+        }
 
         if let Some(ref label_okbutton) = self.label_okbutton {
-            common_options.push("--ok-label");
-            common_options.push(label_okbutton);
+            args.push(if whiptail { "--ok-button" } else { "--ok-label" }.into());
+            push_text(&mut args, label_okbutton);
         }
-        
-        if let Some(ref label_extrabutton) = self.label_extrabutton {
-            common_options.push("--extra-button");
-            common_options.push("--extra-label");
-            common_options.push(label_extrabutton);
+
+        if let Some(label_extrabutton) = self.label_extrabutton.as_ref().filter(|_| !whiptail) {
+            args.push("--extra-button".into());
+            args.push("--extra-label".into());
+            push_text(&mut args, label_extrabutton);
         }
 
         if let Some(ref label_cancelbutton) = self.label_cancelbutton {
-            common_options.push("--cancel-label");
-            common_options.push(label_cancelbutton);
+            args.push(if whiptail { "--cancel-button" } else { "--cancel-label" }.into());
+            push_text(&mut args, label_cancelbutton);
         }
 
-        if let Some(ref label_helpbutton) = self.label_helpbutton {
-            common_options.push("--help-button");
-            common_options.push("--help-label");
-            common_options.push(label_helpbutton);
+        if let Some(label_helpbutton) = self.label_helpbutton.as_ref().filter(|_| !whiptail) {
+            args.push("--help-button".into());
+            args.push("--help-label".into());
+            push_text(&mut args, label_helpbutton);
         }
 
         if !self.cancelbutton {
-            common_options.push("--no-cancel");
+            args.push(if whiptail { "--nocancel" } else { "--no-cancel" }.into());
         }
 
-        if self.insecure {
-            common_options.push("--insecure");
-        } 
+        if self.insecure && !whiptail {
+            args.push("--insecure".into());
+        }
+
+        if self.colors && !whiptail {
+            args.push("--colors".into());
+        }
+
+        if let Some(timeout) = request.timeout().or(self.timeout).filter(|_| !whiptail) {
+            args.push("--timeout".into());
+            args.push(timeout.to_string().into());
+        }
+
+        if request.kind() == BoxKind::Checklist {
+            args.push("--separate-output".into());
+        }
+
+        // The directory of a file selection might not be valid UTF-8.
+        let (boxtype, boxtype_arg): (_, OsString) = match *request {
+            Request::Checklist { ref text, .. } => ("--checklist", text.into()),
+            Request::FileSelection { ref path, .. } => {
+                // Relative paths are resolved in the working directory of dialog.
+                let path = match (path, &self.current_dir) {
+                    (Some(path), Some(dir)) => Some(dir.join(path)),
+                    _ => path.clone(),
+                };
+                ("--fselect", crate::dir_to_os_string(&path)?)
+            }
+            Request::Form { ref text, .. } => ("--form", text.into()),
+            Request::Gauge { ref text, .. } => ("--gauge", text.into()),
+            Request::Input { ref text, .. } => ("--inputbox", text.into()),
+            Request::Menu { ref text, .. } => ("--menu", text.into()),
+            Request::Message { ref text, .. } => ("--msgbox", text.into()),
+            Request::MixedForm { ref text, .. } => ("--mixedform", text.into()),
+            Request::MixedGauge { ref text, .. } => ("--mixedgauge", text.into()),
+            Request::Password { ref text, .. } => ("--passwordbox", text.into()),
+            Request::PasswordForm { ref text, .. } => ("--passwordform", text.into()),
+            Request::Question { ref text, .. } => ("--yesno", text.into()),
+        };
+        let boxtype_index = args.len();
+        args.push(boxtype.into());
+        push_os_text(&mut args, &boxtype_arg);
+        args.push(self.height.clone().into());
+        args.push(self.width.clone().into());
 
-        command.args(common_options);
-        command.arg(boxtype);
-        
-        if let Some(ref boxtype_arg) = boxtype_arg {
-            command.arg(boxtype_arg);
+        match *request {
+            Request::Checklist { list_height, ref list, .. } => {
+                args.push(list_height.to_string().into());
+                for (tag, item, status) in list {
+                    push_text(&mut args, tag);
+                    push_text(&mut args, item);
+                    args.push(if *status { "on" } else { "off" }.into());
+                }
+            }
+            Request::Form { form_height, ref list, .. }
+            | Request::MixedForm { form_height, ref list, .. }
+            | Request::PasswordForm { form_height, ref list, .. } => {
+                push_form_args(&mut args, form_height, list);
+            }
+            Request::Input { default: Some(ref default), .. } => push_text(&mut args, default),
+            Request::Menu { menu_height, ref list, .. } => {
+                args.push(menu_height.to_string().into());
+                for text in list.iter().flatten() {
+                    push_text(&mut args, text);
+                }
+            }
+            Request::MixedGauge { percent, .. } => args.push(percent.to_string().into()),
+            _ => {}
         }
 
-        command.arg(&self.height);
-        command.arg(&self.width);
+        if whiptail {
+            args = whiptail_args(args, boxtype_index);
+        }
+        Ok(args)
+    }
+
+    // Returns the program that is executed, which is either the wrapper or the executable.
+    fn program(&self) -> &str {
+        self.wrapper.first().unwrap_or(&self.executable)
+    }
+
+    // Builds the command line for the given dialog arguments, including the wrapper.  The wrapper
+    // has to pass on the given file descriptors to dialog.
+    fn wrap(&self, args: Vec<OsString>, fds: &[RawFd]) -> Result<CommandLine> {
+        let mut all_args: Vec<OsString> = Vec::new();
+        let mut env = self.env.clone();
+        if let Some((program, wrapper_args)) = self.wrapper.split_first() {
+            let name = Path::new(program).file_name().and_then(OsStr::to_str).unwrap_or_default();
+            if CLOSING_WRAPPERS.contains(&name) {
+                return Err(Error::InvalidConfig(format!(
+                    "{} closes the file descriptors used by dialog and cannot be used as a wrapper",
+                    name
+                )));
+            }
+            all_args.extend(wrapper_args.iter().map(OsString::from));
+            // flatpak-spawn only passes on the file descriptors and the environment variables that
+            // are given as options.
+            if name == "flatpak-spawn" {
+                all_args.extend(fds.iter().map(|fd| format!("--forward-fd={}", fd).into()));
+                all_args.extend(env.drain(..).map(|(key, value)| {
+                    format!("--env={}={}", key, value).into()
+                }));
+            }
+            all_args.push(self.executable.clone().into());
+        }
+        all_args.extend(args);
+        Ok(CommandLine {
+            program: self.program().to_string(),
+            args: all_args,
+            env,
+        })
+    }
+
+    // Creates the command for dialog with the wrapper, the environment and the working directory,
+    // but without the dialog arguments.
+    fn base_command(&self, fds: &[RawFd]) -> Result<process::Command> {
+        let command_line = self.wrap(Vec::new(), fds)?;
+        let mut command = process::Command::new(command_line.program);
+        command.args(command_line.args);
+        command.envs(command_line.env);
+        if let Some(ref dir) = self.current_dir {
+            command.current_dir(dir);
+        }
+        Ok(command)
+    }
+
+    // Creates the command for the given dialog box.  dialog writes the answer to the returned
+    // pipe, which is passed to dialog as the file descriptor OUTPUT_FD.  The write end of the pipe
+    // is owned by the command and closed when the command is dropped.  If the options are passed
+    // in a file, the thread writing the file is returned too.
+    fn command(&self, request: &Request) -> Result<(process::Command, io::PipeReader, FileWriter)> {
+        let mut args = self.args(request)?;
+        let file_supported = self.check_supported(request)?;
+
+        // Sensitive values would be visible to all users in the process list, and long argument
+        // lists exceed the limits of the operating system, so the options are passed in a file.
+        // Older versions of dialog without the --file option, and whiptail, get long options on
+        // the command line.  Dialog boxes with sensitive values are rejected by check_supported.
+        let long = args.iter().map(|arg| arg.len()).sum::<usize>() > MAX_ARGS_LEN;
+        let use_file = (has_secrets(request) || long) && file_supported;
+        let fds: &[RawFd] = if use_file { &[OUTPUT_FD, FILE_FD] } else { &[OUTPUT_FD] };
+        let mut command = self.base_command(fds)?;
+
+        let (reader, writer) = io::pipe()?;
+        // Safety: the closure only calls async-signal-safe functions.
+        unsafe {
+            command.pre_exec(move || inherit_fd(writer.as_raw_fd(), OUTPUT_FD));
+        }
+
+        // The output pipe is still open, so the read end of this pipe cannot be OUTPUT_FD.
+        let mut writer = None;
+        if use_file {
+            let (file_reader, mut file_writer) = io::pipe()?;
+            let contents = file_contents(&args.split_off(2));
+            // Safety: the closure only calls async-signal-safe functions.
+            unsafe {
+                command.pre_exec(move || inherit_fd(file_reader.as_raw_fd(), FILE_FD));
+            }
+            args.push("--file".into());
+            args.push(format!("/dev/fd/{}", FILE_FD).into());
+            // dialog reads the file only after it has been started, so the contents are written
+            // in the background.  If dialog could not be started, the write fails as soon as the
+            // command is dropped.
+            writer = Some(thread::spawn(move || file_writer.write_all(&contents)));
+        }
         command.args(args);
 
-        command.output().map_err(Error::IoError)
+        // If stdin or stdout is redirected, for example to a pipe or a file, dialog has to use the
+        // controlling terminal instead.
+        let stdin = if request.kind() == BoxKind::Gauge {
+            process::Stdio::piped()
+        } else if io::stdin().is_terminal() {
+            process::Stdio::inherit()
+        } else {
+            open_terminal()?.into()
+        };
+        let stdout = if io::stdout().is_terminal() {
+            process::Stdio::inherit()
+        } else {
+            open_terminal()?.into()
+        };
+        command.stdin(stdin);
+        command.stdout(stdout);
+        command.stderr(process::Stdio::piped());
+        Ok((command, reader, writer))
+    }
+
+    // Checks that dialog, or whiptail, supports the given dialog box, and returns whether the
+    // --file option is supported.
+    fn check_supported(&self, request: &Request) -> Result<bool> {
+        if self.whiptail {
+            return match whiptail_unsupported_option(request) {
+                Some(option) => Err(Error::Unsupported(format!(
+                    "whiptail does not support the {} option",
+                    option
+                ))),
+                None => Ok(false),
+            };
+        }
+
+        let version = self.version()?;
+        // If the version is unknown, all options are assumed to be supported.
+        let date = version.as_deref().and_then(version_date);
+        let supported = |since| date.is_none_or(|date| date >= since);
+        if let Some(option) = unsupported_option(request, supported) {
+            return Err(Error::Unsupported(format!(
+                "dialog {} does not support the {} option",
+                version.as_deref().unwrap_or_default(),
+                option
+            )));
+        }
+        Ok(supported(FILE_SINCE))
+    }
+
+    fn show(&self, request: Request) -> Result<Response> {
+        let output = self.execute(request.clone())?;
+        get_response(&request, self.timeout_answer, output)
+    }
+
+    fn execute(&self, request: Request) -> Result<Output> {
+        let (mut command, mut reader, writer) = self.command(&request)?;
+        let mut child = command.spawn().map_err(|err| Error::from((self.program(), err)))?;
+        drop(command);
+
+        if let Request::Gauge { percent, .. } = request {
+            let mut stdin = child.stdin.take().ok_or("dialog stdin not available")?;
+            writeln!(stdin, "{}", percent)?;
+        }
+        // The answer and the diagnostics are read in parallel so that dialog cannot block on a
+        // full pipe.
+        let answer = thread::spawn(move || {
+            let mut answer = Vec::new();
+            reader.read_to_end(&mut answer).map(|_| answer)
+        });
+        let output = child.wait_with_output()?;
+        let answer = answer.join().map_err(|_| "could not read the dialog output")??;
+        join_writer(writer, output.status)?;
+        Ok(Output {
+            command: self.name(),
+            status: output.status,
+            answer,
+            stderr: output.stderr,
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn execute_async(&self, request: &Request) -> Result<Output> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (command, reader, writer) = self.command(request)?;
+        let terminal = Terminal::save();
+        let mut command = tokio::process::Command::from(command);
+        let child = command.spawn().map_err(|err| Error::from((self.program(), err)))?;
+        drop(command);
+        // Close the dialog box if the future is dropped.
+        let mut child = AsyncChild { child, terminal };
+
+        if let Request::Gauge { percent, .. } = *request {
+            let mut stdin = child.child.stdin.take().ok_or("dialog stdin not available")?;
+            stdin.write_all(format!("{}\n", percent).as_bytes()).await?;
+        }
+        let mut stderr = child.child.stderr.take().ok_or("dialog stderr not available")?;
+        let mut reader = tokio::net::unix::pipe::Receiver::from_owned_fd(reader.into())?;
+        let mut answer = Vec::new();
+        let mut errors = Vec::new();
+        let (_, _, status) = tokio::try_join!(
+            reader.read_to_end(&mut answer),
+            stderr.read_to_end(&mut errors),
+            child.child.wait(),
+        )?;
+        // dialog has exited, so the thread does not block anymore.
+        join_writer(writer, status)?;
+        Ok(Output {
+            command: self.name(),
+            status,
+            answer,
+            stderr: errors,
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl super::AsyncBackend for Dialog {
+    fn show_request(&self, request: Request) -> super::BoxFuture<'_, Result<Response>> {
+        Box::pin(async move {
+            let output = self.execute_async(&request).await?;
+            get_response(&request, self.timeout_answer, output)
+        })
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection)
+        -> super::BoxFuture<'_, Result<(Choice, Option<PathBuf>)>> {
+        let request: Request = file_selection.into();
+        Box::pin(async move {
+            // The path is converted from the raw output so that file names that are not valid
+            // UTF-8 are preserved.
+            let output = self.execute_async(&request).await?;
+            get_raw_response(&request, self.timeout_answer, output)
+                .map(request::raw_file_selection_output)
+        })
+    }
+}
+
+// The time that dialog has to exit after it has been asked to close the dialog box.
+#[cfg(feature = "tokio")]
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// A dialog process started by the async backend.  If it is dropped while dialog is still running,
+// dialog is terminated with SIGTERM so that it can leave curses mode, and killed if it does not
+// exit in time.  Afterwards, the terminal settings are restored.
+#[cfg(feature = "tokio")]
+struct AsyncChild {
+    child: tokio::process::Child,
+    terminal: Terminal,
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for AsyncChild {
+    fn drop(&mut self) {
+        if !matches!(self.child.try_wait(), Ok(None)) {
+            return;
+        }
+        if let Some(pid) = self.child.id() {
+            unsafe {
+                libc::kill(pid as libc::pid_t, libc::SIGTERM);
+            }
+        }
+        let deadline = Instant::now() + CLOSE_TIMEOUT;
+        while let Ok(None) = self.child.try_wait() {
+            if Instant::now() >= deadline {
+                let _ = self.child.start_kill();
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        self.terminal.restore();
     }
 }
 
@@ -204,146 +780,719 @@ impl Default for Dialog {
             label_cancelbutton: None,
             label_helpbutton: None,
             insecure: false,
+            colors: false,
             cancelbutton: true,
+            timeout: None,
+            timeout_answer: None,
+            whiptail: false,
+            executable: "dialog".to_string(),
+            wrapper: Vec::new(),
+            env: Vec::new(),
+            current_dir: None,
+            version: OnceLock::new(),
             height: "0".to_string(),
             width: "0".to_string(),
         }
     }
 }
 
-fn require_success(status: process::ExitStatus) -> Result<()> {
-    if status.success() {
+// Opens the controlling terminal of the process.
+fn open_terminal() -> Result<fs::File> {
+    fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|err| {
+            Error::NoTerminal(format!("could not open the controlling terminal /dev/tty: {}", err))
+        })
+}
+
+// The file descriptor that dialog writes the answer to.
+const OUTPUT_FD: RawFd = 3;
+
+// The file descriptor that dialog reads the options from if they are not passed as arguments.
+const FILE_FD: RawFd = 4;
+
+// Wrappers that close all file descriptors except for stdin, stdout and stderr.
+const CLOSING_WRAPPERS: &[&str] = &["doas", "sudo"];
+
+// The first snapshots of dialog that support the options used by this backend.  Older snapshots
+// print their usage if they are called with an unknown option.
+const OUTPUT_FD_SINCE: u32 = 20051207;
+const MIXED_GAUGE_SINCE: u32 = 20070227;
+const FORMS_SINCE: u32 = 20070930;
+const FILE_SINCE: u32 = 20080316;
+
+// The maximum total length of the arguments that are passed on the command line.
+const MAX_ARGS_LEN: usize = 64 * 1024;
+
+// The thread that writes the options for dialog's --file option, if they are passed in a file.
+type FileWriter = Option<thread::JoinHandle<io::Result<()>>>;
+
+// Waits for the thread writing the options file after dialog has exited.  If dialog could not
+// read all options, it reports an error itself, so a failed write is only reported if dialog
+// exited successfully.
+fn join_writer(writer: FileWriter, status: process::ExitStatus) -> Result<()> {
+    if let Some(writer) = writer {
+        let written = writer.join().map_err(|_| "could not write the dialog options")?;
+        if status.success() {
+            written?;
+        }
+    }
+    Ok(())
+}
+
+// Makes the given file descriptor available as the target descriptor in the child process.  This
+// function is called between fork and exec, so it may only use async-signal-safe functions.
+fn inherit_fd(fd: RawFd, target: RawFd) -> io::Result<()> {
+    // dup2 does nothing if the descriptors are equal, so we have to clear FD_CLOEXEC manually.
+    let result = if fd == target {
+        unsafe { libc::fcntl(fd, libc::F_SETFD, 0) }
+    } else {
+        unsafe { libc::dup2(fd, target) }
+    };
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
         Ok(())
+    }
+}
+
+// Extracts the snapshot date from a dialog version like 1.3-20201126.
+fn version_date(version: &str) -> Option<u32> {
+    let (_, date) = version.rsplit_once('-')?;
+    if date.len() == 8 {
+        date.parse().ok()
     } else {
-        Err(Error::from(("dialog", status)))
+        None
+    }
+}
+
+// Returns the first option required for the given dialog box that is not supported.  Sensitive
+// values must not be passed on the command line, so they require the --file option.
+fn unsupported_option(request: &Request, supported: impl Fn(u32) -> bool) -> Option<&'static str> {
+    if !supported(OUTPUT_FD_SINCE) {
+        return Some("--output-fd");
+    }
+    let option = match request.kind() {
+        BoxKind::MixedGauge if !supported(MIXED_GAUGE_SINCE) => Some("--mixedgauge"),
+        BoxKind::MixedForm if !supported(FORMS_SINCE) => Some("--mixedform"),
+        BoxKind::PasswordForm if !supported(FORMS_SINCE) => Some("--passwordform"),
+        _ => None,
+    };
+    option.or_else(|| (has_secrets(request) && !supported(FILE_SINCE)).then_some("--file"))
+}
+
+// Checks whether the dialog box contains values that should not be visible to other users, like
+// defaults and form fields.
+fn has_secrets(request: &Request) -> bool {
+    match *request {
+        Request::Input { ref default, .. } => default.is_some(),
+        Request::Form { ref list, .. }
+        | Request::MixedForm { ref list, .. }
+        | Request::PasswordForm { ref list, .. } => list.iter().any(|field| !field.3.is_empty()),
+        _ => false,
+    }
+}
+
+// The dialog boxes that whiptail can display.
+const WHIPTAIL_BOXES: &[BoxKind] = &[
+    BoxKind::Checklist,
+    BoxKind::Gauge,
+    BoxKind::Input,
+    BoxKind::Menu,
+    BoxKind::Message,
+    BoxKind::Password,
+    BoxKind::Question,
+];
+
+// Returns the option required for the given dialog box that whiptail does not support.
+// whiptail has no --file option, so sensitive values cannot be passed safely.
+fn whiptail_unsupported_option(request: &Request) -> Option<&'static str> {
+    let option = match request.kind() {
+        BoxKind::FileSelection => "--fselect",
+        BoxKind::Form => "--form",
+        BoxKind::MixedForm => "--mixedform",
+        BoxKind::MixedGauge => "--mixedgauge",
+        BoxKind::PasswordForm => "--passwordform",
+        _ if has_secrets(request) => "--file",
+        _ => return None,
+    };
+    Some(option)
+}
+
+// Converts dialog arguments to whiptail arguments.  whiptail parses its options with popt, which
+// takes the argument following an option as its value and treats all arguments after -- as
+// positional.  So the -- that push_text adds in front of text starting with -- is removed, and a
+// single -- is added after the box option at the given index instead.
+fn whiptail_args(args: Vec<OsString>, boxtype_index: usize) -> Vec<OsString> {
+    let mut whiptail_args = Vec::with_capacity(args.len() + 1);
+    let mut args = args.into_iter().enumerate().peekable();
+    while let Some((i, arg)) = args.next() {
+        let escaped = args.peek().is_some_and(|(_, next)| next.as_bytes().starts_with(b"--"));
+        if arg == "--" && escaped {
+            // push_text only adds -- in front of text that starts with --, so the next argument
+            // is text and must not be checked again.
+            let (_, text) = args.next().unwrap_or_default();
+            whiptail_args.push(text);
+        } else {
+            whiptail_args.push(arg);
+        }
+        if i == boxtype_index {
+            whiptail_args.push("--".into());
+        }
     }
+    whiptail_args
 }
 
-fn get_choice(status: process::ExitStatus) -> Result<Choice> {
-    if let Some(code) = status.code() {
-        match code {
-            0 => Ok(Choice::Yes),
-            1 => Ok(Choice::No),
-            255 => Ok(Choice::Escape),
-            _ => Err(Error::from(("dialog", status))),
+// Formats the arguments for dialog's --file option:  each argument is quoted with double quotes,
+// and backslashes and double quotes are escaped with a backslash.
+fn file_contents(args: &[OsString]) -> Vec<u8> {
+    let mut contents = Vec::new();
+    for arg in args {
+        contents.push(b'"');
+        for &c in arg.as_bytes() {
+            if c == b'\\' || c == b'"' {
+                contents.push(b'\\');
+            }
+            contents.push(c);
         }
+        contents.extend_from_slice(b"\"\n");
+    }
+    contents
+}
+
+// The result of a dialog invocation:  the answer written to the output file descriptor and the
+// warnings and error messages written to stderr.
+struct Output {
+    // The name of the program, dialog or whiptail.
+    command: &'static str,
+    status: process::ExitStatus,
+    answer: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+// Creates an error for an unexpected exit status from the error message printed by dialog.
+fn failure(output: &Output) -> Error {
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let message = stderr.to_lowercase();
+    if message.contains("can't make new window") || message.contains("too big")
+        || message.contains("too small") {
+        Error::TerminalTooSmall(stderr)
+    } else if message.contains("error opening terminal") || message.contains("not a tty") {
+        Error::NoTerminal(stderr)
+    } else {
+        Error::CommandFailed {
+            command: output.command.to_string(),
+            status: output.status,
+            stderr,
+        }
+    }
+}
+
+// dialog exits with 255 both if the user pressed ESC and if it could not display the dialog box.
+// Only in the latter case, it prints an error message.
+fn escape_or_failure(output: &Output) -> Result<Choice> {
+    if output.stderr.iter().all(u8::is_ascii_whitespace) {
+        Ok(Choice::Escape)
     } else {
-        Err(Error::from(("dialog", status)))
+        Err(failure(output))
     }
 }
 
-// Gets button choice and item/input choice.
-fn get_choices(output: process::Output) -> Result<(Choice, Option<String>)> {
-    if let Some(code) = output.status.code() {
-        let output_dialog = String::from_utf8(output.stderr).map(Some).unwrap();
-        match code {
-            0 => Ok((Choice::Yes, output_dialog)),
-            1 => Ok((Choice::Cancel, output_dialog)),
-            2 => Ok((Choice::Help, output_dialog)),
-            3 => Ok((Choice::Extra, output_dialog)),
-            255 => Ok((Choice::Escape, output_dialog)),
-            _ => Err(Error::from(("dialog", output.status))),
-        } 
-    } else { 
-        Err(Error::from(("dialog", output.status)))
+// Like require_success, but also accepts the timeout exit status of dialog.
+fn require_closed(output: Output) -> Result<Choice> {
+    match output.status.code() {
+        Some(5) => Ok(Choice::Timeout),
+        _ => require_success(output).map(|_| Choice::Yes),
+    }
+}
+
+fn require_success(output: Output) -> Result<()> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(failure(&output))
+    }
+}
+
+fn get_choice(output: Output) -> Result<Choice> {
+    match output.status.code() {
+        Some(0) => Ok(Choice::Yes),
+        Some(1) => Ok(Choice::No),
+        Some(5) => Ok(Choice::Timeout),
+        Some(255) => escape_or_failure(&output),
+        _ => Err(failure(&output)),
+    }
+}
+
+// Gets button choice and the item/input choice as written by dialog, which might not be valid
+// UTF-8 for file names.
+fn get_raw_choices(output: Output) -> Result<(Choice, Option<Vec<u8>>)> {
+    let choice = match output.status.code() {
+        Some(0) => Choice::Yes,
+        Some(1) => Choice::Cancel,
+        Some(2) => Choice::Help,
+        Some(3) => Choice::Extra,
+        Some(5) => return Ok((Choice::Timeout, None)),
+        Some(255) => escape_or_failure(&output)?,
+        _ => return Err(failure(&output)),
+    };
+    Ok((choice, Some(output.answer)))
+}
+
+// Converts the output of dialog for the given dialog box into the choice and the value as
+// written by dialog.  If an answer is given for timeouts, a timeout is reported as this answer
+// with the initial value of the dialog box.
+fn get_raw_response(request: &Request, timeout_answer: Option<Choice>, output: Output)
+    -> Result<RawResponse> {
+    let (choice, value) = match request.kind() {
+        BoxKind::Gauge | BoxKind::Message | BoxKind::MixedGauge => (require_closed(output)?, None),
+        BoxKind::Question => (get_choice(output)?, None),
+        // With --separate-output, every tag is terminated by a newline.
+        BoxKind::Checklist => {
+            let (choice, value) = get_raw_choices(output)?;
+            let value = value.map(String::from_utf8).transpose()?;
+            let value = value.map(|value| value.lines().collect::<Vec<_>>().join("\n").into_bytes());
+            (choice, value)
+        }
+        _ => get_raw_choices(output)?,
+    };
+    let (choice, value) = match timeout_answer {
+        Some(answer) if choice == Choice::Timeout => {
+            (answer, initial_value(request).map(OsString::into_vec))
+        }
+        _ => (choice, value),
+    };
+    Ok(RawResponse { kind: request.kind(), choice, value })
+}
+
+// Converts the output of dialog for the given dialog box into a response.
+fn get_response(request: &Request, timeout_answer: Option<Choice>, output: Output)
+    -> Result<Response> {
+    get_raw_response(request, timeout_answer, output)?.into_response()
+}
+
+// Returns the value that dialog would return if the user confirmed the dialog box without
+// changing it.
+fn initial_value(request: &Request) -> Option<OsString> {
+    let value = match *request {
+        Request::Checklist { ref list, .. } => {
+            let tags = list.iter().filter(|(_, _, status)| *status).map(|(tag, _, _)| tag.as_str());
+            Some(tags.collect::<Vec<_>>().join("\n"))
+        }
+        Request::FileSelection { ref path, .. } => return crate::dir_to_os_string(path).ok(),
+        Request::Form { ref list, .. }
+        | Request::MixedForm { ref list, .. }
+        | Request::PasswordForm { ref list, .. } => {
+            Some(list.iter().map(|field| format!("{}\n", field.3)).collect())
+        }
+        Request::Input { ref default, .. } => Some(default.clone().unwrap_or_default()),
+        Request::Menu { ref list, .. } => list.first().map(|item| item[0].clone()),
+        Request::Password { .. } => Some(String::new()),
+        _ => None,
+    };
+    value.map(OsString::from)
+}
+
+// Flattens the fields of a form into the arguments expected by dialog.
+fn push_form_args(args: &mut Vec<OsString>, form_height: u32, list: &[FormField]) {
+    args.push(form_height.to_string().into());
+    for (label, label_y, label_x, item, item_y, item_x, flen, ilen) in list {
+        push_text(args, label);
+        args.push(label_y.to_string().into());
+        args.push(label_x.to_string().into());
+        push_text(args, item);
+        args.push(item_y.to_string().into());
+        args.push(item_x.to_string().into());
+        args.push(flen.to_string().into());
+        args.push(ilen.to_string().into());
+    }
+}
+
+// Adds text that might be controlled by the user to the arguments.  Control characters except
+// newlines and tabs are removed so that they cannot manipulate the terminal.  dialog reads every
+// argument starting with -- as an option unless it is preceded by --, so such text is escaped.
+fn push_text(args: &mut Vec<OsString>, text: &str) {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect();
+    if text.starts_with("--") {
+        args.push("--".into());
+    }
+    args.push(text.into());
+}
+
+// Like push_text, but also accepts text that is not valid UTF-8.  In this case, ASCII control
+// characters except newlines and tabs are removed.
+fn push_os_text(args: &mut Vec<OsString>, text: &OsStr) {
+    if let Some(text) = text.to_str() {
+        return push_text(args, text);
+    }
+    let text: Vec<u8> = text
+        .as_bytes()
+        .iter()
+        .copied()
+        .filter(|c| !c.is_ascii_control() || *c == b'\n' || *c == b'\t')
+        .collect();
+    if text.starts_with(b"--") {
+        args.push("--".into());
+    }
+    args.push(OsString::from_vec(text));
+}
+
+/// A command line used by the [`Dialog`][] backend.
+///
+/// The command line can be printed with the [`Display`][] implementation, which quotes the
+/// arguments for a POSIX shell and prefixes the command with the environment variables.
+///
+/// [`Dialog`]: struct.Dialog.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandLine {
+    program: String,
+    args: Vec<OsString>,
+    env: Vec<(String, String)>,
+}
+
+impl CommandLine {
+    /// Returns the program that is executed.
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// Returns the arguments passed to the program.
+    ///
+    /// The directory of a file selection might not be valid UTF-8, so the arguments are OS
+    /// strings.
+    pub fn args(&self) -> &[OsString] {
+        &self.args
+    }
+
+    /// Returns the environment variables that are set for the program.
+    pub fn env(&self) -> &[(String, String)] {
+        &self.env
+    }
+}
+
+impl fmt::Display for CommandLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (key, value) in &self.env {
+            write!(f, "{}={} ", key, shell_quote(value))?;
+        }
+        write!(f, "{}", shell_quote(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", shell_quote(&arg.to_string_lossy()))?;
+        }
+        Ok(())
+    }
+}
+
+// Quotes the given string for a POSIX shell if necessary.
+pub(crate) fn shell_quote(s: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-+=.,:/@%".contains(c);
+    if !s.is_empty() && s.chars().all(safe) {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
     }
 }
 
 impl super::Backend for Dialog {
-    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<String>)> {
-        let dir = file_selection.path_to_string().ok_or("path not valid")?;
-        self.execute("--fselect", &Some(dir), vec![], None)
-            .and_then(get_choices)
+    fn capabilities(&self) -> Capabilities {
+        if self.whiptail {
+            return Capabilities::with_boxes(WHIPTAIL_BOXES);
+        }
+        let mut capabilities = Capabilities::all_boxes();
+        capabilities.extra_button = true;
+        capabilities.help_button = true;
+        capabilities.colors = true;
+        capabilities.timeout = true;
+        capabilities
+    }
+
+    fn can_run(&self) -> bool {
+        super::is_available(self.program()) && super::has_terminal()
+    }
+
+    fn spawn(&self, request: Request) -> Result<Running> {
+        let (mut command, reader, writer) = self.command(&request)?;
+        let terminal = Terminal::save();
+        let mut child = command.spawn().map_err(|err| Error::from((self.program(), err)))?;
+        drop(command);
+
+        if let Request::Gauge { percent, .. } = request {
+            let mut stdin = child.stdin.take().ok_or("dialog stdin not available")?;
+            writeln!(stdin, "{}", percent)?;
+        }
+        let timeout_answer = self.timeout_answer;
+        let command = self.name();
+        let parse = Box::new(move |status, answer, stderr| {
+            join_writer(writer, status)?;
+            let output = Output { command, status, answer, stderr };
+            get_raw_response(&request, timeout_answer, output)
+        });
+        Ok(Running::new(child, reader, parse, terminal))
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        self.show(checklist.into())
+            .map(request::checklist_output)
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        // The path is converted from the raw output so that file names that are not valid UTF-8
+        // are preserved.
+        let request: Request = file_selection.into();
+        let output = self.execute(request.clone())?;
+        get_raw_response(&request, self.timeout_answer, output)
+            .map(request::raw_file_selection_output)
     }
 
     fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
-        let mut args: Vec<&str> = Vec::new();
-        let form_height: String = form.form_height.to_string();
-        args.push(form_height.as_str());
-        let form_list :Vec<&str> = form.list.iter().map(AsRef::as_ref).collect(); 
-        args.extend(form_list);
- 
-        self.execute("--form", &Some(form.text.clone()), args, None)
-            .and_then(get_choices)
+        self.show(form.into())
+            .map(request::choices_output)
     }
 
     fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
-        let echo_child = process::Command::new("echo")
-        .arg(gauge.percent.to_string())
-        .stdout(process::Stdio::piped())
-        .spawn()
-        .expect("Failed to execute echo command"); 
-
-        self.execute("--gauge", &Some(gauge.text.clone()), vec![], Some(echo_child))
-            .and_then(|output| require_success(output.status))
-            .map(|_| ())
+        self.show(gauge.into())
+            .map(request::unit_output)
     }
 
     fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
-        let mut args: Vec<&str> = Vec::new();
-        if let Some(ref default) = input.default {
-            args.push(default);
-        }
-        self.execute("--inputbox", &Some(input.text.clone()), args, None)
-            .and_then(get_choices)
+        self.show(input.into())
+            .map(request::choices_output)
     }
 
     fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
-        let mut args: Vec<&str> = Vec::new();
-        let menu_height: String = menu.menu_height.to_string();
-        args.push(menu_height.as_str());
-        let menu_list :Vec<&str> = menu.list.iter().map(AsRef::as_ref).collect(); 
-        args.extend(menu_list);
-
-        self.execute("--menu", &Some(menu.text.clone()), args, None)
-            .and_then(get_choices)
+        self.show(menu.into())
+            .map(request::choices_output)
     }
 
     fn show_message(&self, message: &Message) -> Result<()> {
-        self.execute("--msgbox", &Some(message.text.clone()), vec![], None)
-            .and_then(|output| require_success(output.status))
-            .map(|_| ())
+        self.show(message.into())
+            .map(request::unit_output)
     }
 
     fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
-        let mut args: Vec<&str> = Vec::new();
-        let gauge_percent: String = gauge.percent.to_string();
-        args.push(gauge_percent.as_str());
-
-        self.execute("--mixedgauge", &Some(gauge.text.clone()), args, None)
-            .and_then(|output| require_success(output.status))
-            .map(|_| ())
+        self.show(gauge.into())
+            .map(request::unit_output)
     }
 
     fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
-        let mut args: Vec<&str> = Vec::new();
-        let form_height: String = form.form_height.to_string();
-        args.push(form_height.as_str());
-        let form_list :Vec<&str> = form.list.iter().map(AsRef::as_ref).collect(); 
-        args.extend(form_list);
- 
-        self.execute("--mixedform", &Some(form.text.clone()), args, None)
-            .and_then(get_choices)
+        self.show(form.into())
+            .map(request::choices_output)
     }
 
     fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
-        self.execute("--passwordbox", &Some(password.text.clone()), vec![], None)
-            .and_then(get_choices)
+        self.show(password.into())
+            .map(request::choices_output)
     }
 
     fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
-        let mut args: Vec<&str> = Vec::new();
-        let form_height: String = form.form_height.to_string();
-        args.push(form_height.as_str());
-        let form_list :Vec<&str> = form.list.iter().map(AsRef::as_ref).collect(); 
-        args.extend(form_list);
- 
-        self.execute("--passwordform", &Some(form.text.clone()), args, None)
-            .and_then(get_choices)
+        self.show(form.into())
+            .map(request::choices_output)
     }
 
     fn show_question(&self, question: &Question) -> Result<Choice> {
-        self.execute("--yesno", &Some(question.text.clone()), vec![], None)
-            .and_then(|output| get_choice(output.status))
+        self.show(question.into())
+            .map(request::choice_output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+
+    use super::*;
+    use crate::{
+        Checklist, FileSelection, Form, Input, Menu, Message, MixedGauge, PasswordForm, Question,
+    };
+
+    fn output(code: i32, answer: &[u8], stderr: &str) -> Output {
+        Output {
+            command: "dialog",
+            status: process::ExitStatus::from_raw(code << 8),
+            answer: answer.to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn failure_is_mapped_from_stderr() {
+        let err = failure(&output(255, b"", "Error: Can't make new window at (0,0), size (5,80).\n"));
+        assert!(matches!(err, Error::TerminalTooSmall(_)));
+        let err = failure(&output(255, b"", "Error opening terminal: unknown.\n"));
+        assert!(matches!(err, Error::NoTerminal(_)));
+        match failure(&output(255, b"", "Error: Expected at least 6 tokens\n")) {
+            Error::CommandFailed { command, status, stderr } => {
+                assert_eq!(command, "dialog");
+                assert_eq!(status.code(), Some(255));
+                assert_eq!(stderr, "Error: Expected at least 6 tokens");
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn get_choice_maps_exit_status() {
+        assert_eq!(get_choice(output(0, b"", "")).unwrap(), Choice::Yes);
+        assert_eq!(get_choice(output(1, b"", "")).unwrap(), Choice::No);
+        assert_eq!(get_choice(output(5, b"", "")).unwrap(), Choice::Timeout);
+        assert_eq!(get_choice(output(255, b"", "")).unwrap(), Choice::Escape);
+        assert_eq!(get_choice(output(255, b"", "\n")).unwrap(), Choice::Escape);
+        let err = get_choice(output(255, b"", "Error: Unknown option --foo\n")).unwrap_err();
+        assert!(matches!(err, Error::CommandFailed { .. }));
+        let err = get_choice(output(255, b"", "Error: Can't make new window")).unwrap_err();
+        assert!(matches!(err, Error::TerminalTooSmall(_)));
+        let err = get_choice(output(3, b"", "")).unwrap_err();
+        assert!(matches!(err, Error::CommandFailed { .. }));
+    }
+
+    #[test]
+    fn get_raw_choices_maps_exit_status() {
+        let answer = |code| get_raw_choices(output(code, b"answer", "")).unwrap();
+        assert_eq!(answer(0), (Choice::Yes, Some(b"answer".to_vec())));
+        assert_eq!(answer(1), (Choice::Cancel, Some(b"answer".to_vec())));
+        assert_eq!(answer(2), (Choice::Help, Some(b"answer".to_vec())));
+        assert_eq!(answer(3), (Choice::Extra, Some(b"answer".to_vec())));
+        assert_eq!(answer(5), (Choice::Timeout, None));
+        assert_eq!(answer(255), (Choice::Escape, Some(b"answer".to_vec())));
+        let err = get_raw_choices(output(255, b"", "Error: Unknown option --foo")).unwrap_err();
+        assert!(matches!(err, Error::CommandFailed { .. }));
+        let err = get_raw_choices(output(255, b"", "Error opening terminal: dumb")).unwrap_err();
+        assert!(matches!(err, Error::NoTerminal(_)));
+        let err = get_raw_choices(output(4, b"", "")).unwrap_err();
+        assert!(matches!(err, Error::CommandFailed { .. }));
+    }
+
+    #[test]
+    fn non_utf8_file_names_are_preserved() {
+        let path = b"/tmp/caf\xe9/".to_vec();
+        let (choice, value) = get_raw_choices(output(0, &path, "")).unwrap();
+        assert_eq!((choice, value.as_deref()), (Choice::Yes, Some(&path[..])));
+
+        let request = Request::from(&FileSelection::new("Choose"));
+        let response = get_raw_response(&request, None, output(0, &path, "")).unwrap();
+        let (choice, value) = request::raw_file_selection_output(response);
+        assert_eq!(choice, Choice::Yes);
+        assert_eq!(value.unwrap().into_os_string().into_vec(), path);
+
+        // Responses cannot contain the raw file name, so it is converted lossily.
+        let response = get_response(&request, None, output(0, &path, "")).unwrap();
+        assert_eq!(response.value.as_deref(), Some("/tmp/caf\u{fffd}/"));
+        // Other dialog boxes still require UTF-8.
+        let request = Request::from(&Input::new("Name"));
+        assert!(get_response(&request, None, output(0, &path, "")).is_err());
+    }
+
+    #[test]
+    fn version_date_is_parsed() {
+        assert_eq!(version_date("1.3-20201126"), Some(20201126));
+        assert_eq!(version_date("1.1-20080819"), Some(20080819));
+        assert_eq!(version_date("1.3"), None);
+        assert_eq!(version_date("1.3-2020"), None);
+        assert_eq!(version_date("1.3-2020112x"), None);
+        assert_eq!(version_date("garbage"), None);
+        assert_eq!(version_date(""), None);
+    }
+
+    #[test]
+    fn unsupported_options_are_detected() {
+        fn unsupported(version: &str, request: impl Into<Request>) -> Option<&'static str> {
+            let date = version_date(version);
+            unsupported_option(&request.into(), |since| date.is_none_or(|date| date >= since))
+        }
+
+        let secret = Request::from(&*Input::new("Name").default("s3cret"));
+        let list = vec![("PIN".to_string(), 1, 1, "1234".to_string(), 1, 10, 10, 0)];
+        let form = Request::from(&PasswordForm::new("Login", 1, list));
+        for version in ["1.3-20201126", "1.1-20080819", "garbage"] {
+            assert_eq!(unsupported(version, secret.clone()), None);
+            assert_eq!(unsupported(version, form.clone()), None);
+            assert_eq!(unsupported(version, &MixedGauge::new("Loading", 50)), None);
+        }
+        assert_eq!(unsupported("1.1-20080316", secret.clone()), None);
+        assert_eq!(unsupported("1.1-20080315", secret.clone()), Some("--file"));
+        assert_eq!(unsupported("1.1-20080315", &Input::new("Name")), None);
+        assert_eq!(unsupported("1.1-20070930", form.clone()), Some("--file"));
+        assert_eq!(unsupported("1.1-20070929", form.clone()), Some("--passwordform"));
+        assert_eq!(unsupported("1.1-20070226", &MixedGauge::new("Loading", 50)),
+            Some("--mixedgauge"));
+        assert_eq!(unsupported("1.0-20051206", &Message::new("Hello")), Some("--output-fd"));
+    }
+
+    #[test]
+    fn file_contents_are_quoted() {
+        let args: Vec<OsString> = ["plain", "with space", "say \"hi\"", "C:\\dir\\", "--", ""]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        let expected = r#""plain"
+"with space"
+"say \"hi\""
+"C:\\dir\\"
+"--"
+""
+"#;
+        assert_eq!(file_contents(&args), expected.as_bytes());
+    }
+
+    #[test]
+    fn timeout_is_reported_without_answer() {
+        let input = Request::from(&*Input::new("Name").default("localhost"));
+        let response = get_response(&input, None, output(5, b"", "")).unwrap();
+        assert_eq!(response, Response::new(Choice::Timeout, None));
+        let message = Request::from(&Message::new("Hello"));
+        let response = get_response(&message, None, output(5, b"", "")).unwrap();
+        assert_eq!(response, Response::new(Choice::Timeout, None));
+        let question = Request::from(&Question::new("Continue?"));
+        let response = get_response(&question, None, output(5, b"", "")).unwrap();
+        assert_eq!(response, Response::new(Choice::Timeout, None));
+    }
+
+    #[test]
+    fn timeout_answer_uses_initial_value() {
+        let input = Request::from(&*Input::new("Name").default("localhost"));
+        let response = get_response(&input, Some(Choice::Yes), output(5, b"", "")).unwrap();
+        assert_eq!(response, Response::new(Choice::Yes, Some("localhost".to_string())));
+        let question = Request::from(&Question::new("Continue?"));
+        let response = get_response(&question, Some(Choice::No), output(5, b"", "")).unwrap();
+        assert_eq!(response, Response::new(Choice::No, None));
+        // Other exit statuses are not affected.
+        let response = get_response(&input, Some(Choice::Yes), output(1, b"", "")).unwrap();
+        assert_eq!(response, Response::new(Choice::Cancel, Some(String::new())));
+    }
+
+    fn initial(request: impl Into<Request>) -> Option<String> {
+        initial_value(&request.into()).map(|value| value.into_string().unwrap())
+    }
+
+    #[test]
+    fn initial_value_matches_dialog_output() {
+        let checklist = Checklist::new("Choose", 3, vec![
+            ("a".to_string(), "First".to_string(), true),
+            ("b".to_string(), "Second".to_string(), false),
+            ("c".to_string(), "Third".to_string(), true),
+        ]);
+        assert_eq!(initial(&checklist).as_deref(), Some("a\nc"));
+        let menu = Menu::new("Choose", 2, vec![
+            ["x".to_string(), "First".to_string()],
+            ["y".to_string(), "Second".to_string()],
+        ]);
+        assert_eq!(initial(&menu).as_deref(), Some("x"));
+        let form = Form::new("Login", 2, vec![
+            ("User".to_string(), 1, 1, "root".to_string(), 1, 10, 20, 0),
+            ("Host".to_string(), 2, 1, String::new(), 2, 10, 20, 0),
+        ]);
+        assert_eq!(initial(&form).as_deref(), Some("root\n\n"));
+        let input = Input::new("Name");
+        assert_eq!(initial(&input).as_deref(), Some(""));
+        let dir = std::env::temp_dir();
+        let file_selection = Request::from(&*FileSelection::new("Choose").path(&dir));
+        let expected = format!("{}/", dir.display());
+        assert_eq!(initial(file_selection), Some(expected));
+        assert_eq!(initial(&Message::new("Hello")), None);
     }
 }
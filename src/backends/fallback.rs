@@ -0,0 +1,165 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::backends::{Backend, Capabilities};
+use crate::{
+    Checklist, Choice, Error,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Result
+};
+
+/// The `fallback` backend.
+///
+/// This backend tries an ordered list of backends.  If a backend cannot display a dialog box
+/// because its executable is missing or because it does not support the dialog box, the next
+/// backend in the list is used.  All other errors are returned immediately.
+///
+/// A fallback backend can also be selected by setting the `DIALOG` environment variable to a
+/// colon-separated list of backend names, for example `DIALOG=fzf:dialog:stdio`, see
+/// [`default_backend`][].
+///
+/// [`default_backend`]: ../fn.default_backend.html
+///
+/// # Example
+///
+/// ```no_run
+/// use dialog::backends::{Dialog, Fallback, Stdio};
+/// use dialog::DialogBox;
+///
+/// let backend = Fallback::new(vec![Box::new(Dialog::new()), Box::new(Stdio::new())]);
+/// dialog::Message::new("Did you know that I am using the dialog crate?")
+///     .show_with(&backend)
+///     .expect("Could not display dialog box");
+/// ```
+pub struct Fallback {
+    backends: Vec<Box<dyn Backend>>,
+}
+
+impl Fallback {
+    /// Creates a new `Fallback` instance that tries the given backends in order.
+    pub fn new(backends: Vec<Box<dyn Backend>>) -> Fallback {
+        Fallback { backends }
+    }
+
+    /// Appends a backend to the end of the list.
+    pub fn push(mut self, backend: Box<dyn Backend>) -> Fallback {
+        self.backends.push(backend);
+        self
+    }
+
+    fn try_each<T>(&self, show: impl Fn(&dyn Backend) -> Result<T>) -> Result<T> {
+        let mut last_error = Error::Error("no backend available".to_string());
+        for backend in &self.backends {
+            match show(backend.as_ref()) {
+                Err(err) if is_unavailable(&err) => last_error = err,
+                result => return result,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+// Checks whether the error means that the backend cannot be used for the dialog box at all.
+fn is_unavailable(error: &Error) -> bool {
+    match *error {
+        Error::IoError(ref err) => err.kind() == io::ErrorKind::NotFound,
+        Error::NotFound(_) | Error::NoTerminal(_) | Error::Unsupported(_) => true,
+        _ => false,
+    }
+}
+
+impl fmt::Debug for Fallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Backends do not implement Debug, so only their capabilities are shown
+        let backends: Vec<_> = self.backends.iter().map(|backend| backend.capabilities()).collect();
+        f.debug_struct("Fallback").field("backends", &backends).finish()
+    }
+}
+
+impl AsRef<Fallback> for Fallback {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl Backend for Fallback {
+    // Supports the dialog box types supported by any backend that can be used, and the optional
+    // features of the first backend that can be used.
+    fn capabilities(&self) -> Capabilities {
+        let mut backends = self.backends.iter()
+            .filter(|backend| backend.can_run())
+            .map(|backend| backend.capabilities());
+        let mut capabilities = match backends.next() {
+            Some(capabilities) => capabilities,
+            None => return Capabilities::with_boxes(&[]),
+        };
+        for other in backends {
+            for kind in other.boxes {
+                if !capabilities.supports(kind) {
+                    capabilities.boxes.push(kind);
+                }
+            }
+        }
+        capabilities
+    }
+
+    fn can_run(&self) -> bool {
+        self.backends.iter().any(|backend| backend.can_run())
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        self.try_each(|backend| backend.show_checklist(checklist))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.try_each(|backend| backend.show_file_selection(file_selection))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.try_each(|backend| backend.show_form(form))
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.try_each(|backend| backend.show_gauge(gauge))
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.try_each(|backend| backend.show_input(input))
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        self.try_each(|backend| backend.show_menu(menu))
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.try_each(|backend| backend.show_message(message))
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.try_each(|backend| backend.show_mixed_form(form))
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.try_each(|backend| backend.show_mixed_gauge(gauge))
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.try_each(|backend| backend.show_password(password))
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.try_each(|backend| backend.show_password_form(form))
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.try_each(|backend| backend.show_question(question))
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backends::{Backend, Capabilities};
+use crate::{
+    Checklist, Choice, Error,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Request, Response, Result
+};
+
+/// A reply sent by a frontend process: either the answer of the user or an error message.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Reply {
+    /// The answer of the user.
+    Response(Response),
+    /// An error that occurred while displaying the dialog box.
+    Error {
+        /// The error message.
+        error: String,
+    },
+}
+
+impl From<Result<Response>> for Reply {
+    fn from(result: Result<Response>) -> Reply {
+        match result {
+            Ok(response) => Reply::Response(response),
+            Err(error) => Reply::Error {
+                error: error.to_string(),
+            },
+        }
+    }
+}
+
+// Sends a request using the JSON lines protocol and reads the reply.
+pub(crate) fn send_request(writer: &mut impl Write, reader: &mut impl BufRead, request: &Request)
+    -> Result<Response> {
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()?;
+
+    line.clear();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(Error::Error("connection closed without a reply".to_string()));
+    }
+    match serde_json::from_str(&line)? {
+        Reply::Response(response) => Ok(response),
+        Reply::Error { error } => Err(Error::Error(error)),
+    }
+}
+
+// Reads requests using the JSON lines protocol until the input is closed and answers them using
+// the given backend.
+pub(crate) fn serve_requests(reader: impl BufRead, writer: &mut impl Write, backend: &dyn Backend)
+    -> Result<()> {
+    for line in reader.lines() {
+        let reply = match serde_json::from_str::<Request>(&line?) {
+            Ok(request) => Reply::from(request.show_with(backend)),
+            Err(err) => Reply::Error {
+                error: format!("invalid request: {}", err),
+            },
+        };
+        serde_json::to_writer(&mut *writer, &reply)?;
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+// The time that the frontend process has to exit after its standard input has been closed.
+const EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+struct Connection {
+    child: process::Child,
+    stdin: Option<process::ChildStdin>,
+    stdout: io::BufReader<process::ChildStdout>,
+}
+
+/// The `frontend` backend.
+///
+/// This backend sends dialog boxes to an external frontend process that displays them, for
+/// example a GUI application that does not link this crate.  The frontend process is started
+/// when the backend is created and communicates with the backend over its standard input and
+/// output using the following JSON lines protocol:
+///
+/// - For every dialog box, the backend writes one line containing the JSON serialization of a
+///   [`Request`][] to the frontend's standard input.  The `type` field contains the dialog box
+///   type in snake case, for example `"menu"` or `"password_form"`, and the other fields contain
+///   the text and the options of the dialog box:
+///   ```text
+///   {"type":"menu","text":"Please choose","menu_height":10,"list":[["a","First"],["b","Second"]]}
+///   ```
+/// - The frontend answers every request, including messages and gauges, with exactly one line
+///   on its standard output.  The line contains either a [`Response`][] with the chosen button
+///   (`"yes"`, `"no"`, `"cancel"`, `"extra"`, `"help"` or `"escape"`, or `"timeout"` if the
+///   dialog box timed out) and the optional value, or an error message:
+///   ```text
+///   {"choice":"yes","value":"a"}
+///   {"error":"no display available"}
+///   ```
+///   The value of a checklist response contains one selected tag per line.  Form values contain
+///   one field value per line.
+/// - When the backend is dropped, it closes the frontend's standard input.  The frontend should
+///   then exit.  If it is still running after five seconds, it is killed.
+///
+/// The standard error of the frontend is inherited.  The `frontend` example in the repository
+/// is a reference frontend that answers every dialog box with its default value, which is useful
+/// for testing.  Frontends written in Rust can use [`Request::show_with`][] to display the
+/// requests with one of the other backends and [`Reply`][] to serialize the result.
+///
+/// # Example
+///
+/// ```no_run
+/// use dialog::DialogBox;
+///
+/// let backend = dialog::backends::Frontend::new("my-gui-frontend", &["--theme", "dark"])
+///     .expect("Could not start the frontend");
+/// dialog::Message::new("Did you know that I am using the dialog crate?")
+///     .show_with(&backend)
+///     .expect("Could not display dialog box");
+/// ```
+///
+/// [`Reply`]: enum.Reply.html
+/// [`Request`]: ../enum.Request.html
+/// [`Request::show_with`]: ../enum.Request.html#method.show_with
+/// [`Response`]: ../struct.Response.html
+#[derive(Debug)]
+pub struct Frontend {
+    connection: RefCell<Connection>,
+}
+
+impl Frontend {
+    /// Starts the given frontend program with the given arguments.
+    pub fn new<S: AsRef<OsStr>>(program: impl AsRef<OsStr>, args: &[S]) -> Result<Frontend> {
+        let mut command = process::Command::new(program);
+        command.args(args);
+        Frontend::spawn(command)
+    }
+
+    /// Starts the frontend using the given command.
+    ///
+    /// The standard input and output of the command are replaced by pipes.
+    pub fn spawn(mut command: process::Command) -> Result<Frontend> {
+        let program = command.get_program().to_string_lossy().into_owned();
+        let mut child = command
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .spawn()
+            .map_err(|err| Error::from((program.as_str(), err)))?;
+        let stdin = child.stdin.take().ok_or("frontend stdin not available")?;
+        let stdout = child.stdout.take().ok_or("frontend stdout not available")?;
+        Ok(Frontend {
+            connection: RefCell::new(Connection {
+                child,
+                stdin: Some(stdin),
+                stdout: io::BufReader::new(stdout),
+            }),
+        })
+    }
+
+    fn show(&self, request: Request) -> Result<Response> {
+        let mut connection = self.connection.borrow_mut();
+        let Connection { ref mut stdin, ref mut stdout, .. } = *connection;
+        let stdin = stdin.as_mut().ok_or("frontend stdin not available")?;
+        send_request(stdin, stdout, &request)
+    }
+
+    fn show_choices(&self, request: Request) -> Result<(Choice, Option<String>)> {
+        self.show(request).map(|response| (response.choice, response.value))
+    }
+}
+
+impl AsRef<Frontend> for Frontend {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl Drop for Frontend {
+    fn drop(&mut self) {
+        let connection = self.connection.get_mut();
+        // Closing stdin tells the frontend to exit
+        connection.stdin.take();
+        let deadline = Instant::now() + EXIT_TIMEOUT;
+        while let Ok(None) = connection.child.try_wait() {
+            if Instant::now() >= deadline {
+                let _ = connection.child.kill();
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = connection.child.wait();
+    }
+}
+
+impl Backend for Frontend {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::all_boxes()
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let (choice, value) = self.show_choices(checklist.into())?;
+        let tags = value.map(|value| value.lines().map(ToString::to_string).collect());
+        Ok((choice, tags))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.show_choices(file_selection.into())
+            .map(|(choice, path)| (choice, path.map(PathBuf::from)))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.show(gauge.into()).map(|_| ())
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.show_choices(input.into())
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        self.show_choices(menu.into())
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.show(message.into()).map(|_| ())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.show(gauge.into()).map(|_| ())
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.show_choices(password.into())
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.show(question.into()).map(|response| response.choice)
+    }
+}
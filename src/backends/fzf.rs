@@ -0,0 +1,329 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+
+use crate::backends::{Backend, Capabilities, Stdio};
+use crate::{
+    Checklist, Choice, Error,
+    FileSelection, FileSelectionMode, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Result
+};
+
+/// The `fzf` backend.
+///
+/// This backend uses the external `fzf` program to display menus, checklists and file selections
+/// with fuzzy filtering, which makes it well suited for long lists.  All other dialog boxes are
+/// displayed as plain prompts using the [`Stdio`][] backend.
+///
+/// fzf does not support preselected items, so the initial status of checklist items is ignored.
+///
+/// [`Stdio`]: struct.Stdio.html
+#[derive(Debug, Default)]
+pub struct Fzf {
+    height: Option<String>,
+    fallback: Stdio,
+}
+
+impl Fzf {
+    /// Creates a new `Fzf` instance without configuration.
+    pub fn new() -> Fzf {
+        Default::default()
+    }
+
+    /// Sets the height of the fzf window.
+    ///
+    /// The height is given in lines.  Per default, fzf uses the full screen.
+    pub fn set_height(mut self, height: u32) -> Fzf {
+        self.height = Some(height.to_string());
+        self
+    }
+
+    fn execute<F>(&self, header: &str, args: Vec<&str>, dir: Option<&Path>, input: F)
+        -> Result<process::Output>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()> + Send + 'static,
+    {
+        let mut command = process::Command::new("fzf");
+        command.arg("--header").arg(header);
+        if let Some(ref height) = self.height {
+            command.arg("--height").arg(height);
+        }
+        command.args(args);
+        if let Some(dir) = dir {
+            command.current_dir(dir);
+        }
+
+        let mut child = command
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::inherit())
+            .spawn()
+            .map_err(|err| Error::from(("fzf", err)))?;
+
+        let mut stdin = child.stdin.take().ok_or("fzf stdin not available")?;
+        // fzf reads its input while it is running, so we have to feed it from another thread.
+        // Write errors are expected if the user makes a choice before all items are written.
+        let writer = thread::spawn(move || {
+            let _ = input(&mut stdin);
+        });
+        let output = child.wait_with_output()?;
+        let _ = writer.join();
+        Ok(output)
+    }
+}
+
+impl AsRef<Fzf> for Fzf {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+// Maps fzf's exit status to a choice.  fzf exits with 1 if there was no match and with 130 if
+// the user aborted the selection.
+fn get_choice(status: process::ExitStatus) -> Result<Choice> {
+    match status.code() {
+        Some(0) => Ok(Choice::Yes),
+        Some(1) => Ok(Choice::Cancel),
+        Some(130) => Ok(Choice::Escape),
+        _ => Err(Error::from(("fzf", status))),
+    }
+}
+
+fn get_lines(output: process::Output) -> Result<(Choice, Vec<String>)> {
+    let (choice, lines) = get_raw_lines(output, b'\n')?;
+    let lines = lines.into_iter().map(String::from_utf8).collect::<std::result::Result<_, _>>()?;
+    Ok((choice, lines))
+}
+
+// Like get_lines, but splits the output at the given separator and keeps the lines as bytes, as
+// file names might not be valid UTF-8.
+fn get_raw_lines(output: process::Output, separator: u8) -> Result<(Choice, Vec<Vec<u8>>)> {
+    let choice = get_choice(output.status)?;
+    let mut lines: Vec<Vec<u8>> = output.stdout
+        .split(|&b| b == separator)
+        .map(<[u8]>::to_vec)
+        .collect();
+    // The last line is terminated by the separator too.
+    if lines.last().is_some_and(Vec::is_empty) {
+        lines.pop();
+    }
+    Ok((choice, lines))
+}
+
+// Writes the names of all non-hidden files in dir, each terminated by a null byte so that names
+// with newlines or invalid UTF-8 are passed on unchanged.  Subdirectories are not listed, as they
+// cannot be selected and walking them could take a long time, for example for the home directory.
+fn list_files(dir: &Path, out: &mut dyn Write) -> io::Result<()> {
+    let mut names: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_type().map(|t| t.is_dir()).unwrap_or(true))
+        .map(|entry| entry.file_name())
+        .filter(|name| !name.as_bytes().starts_with(b"."))
+        .collect();
+    names.sort();
+
+    for name in names {
+        out.write_all(name.as_bytes())?;
+        out.write_all(b"\0")?;
+    }
+    Ok(())
+}
+
+// Returns the path selected in a file selection from the lines printed by fzf.  In save mode, the
+// first line is the query, which is used as the file name if it does not match a file.
+fn selected_path(dir: &Path, choice: Choice, mut lines: Vec<Vec<u8>>, save: bool)
+    -> (Choice, Option<PathBuf>) {
+    let query = if save && !lines.is_empty() { Some(lines.remove(0)) } else { None };
+    let name = match (choice, lines.pop(), query) {
+        (Choice::Yes, Some(name), _) => name,
+        (Choice::Cancel, None, Some(query)) if !query.is_empty() => query,
+        (choice, _, _) => return (choice, None),
+    };
+    (Choice::Yes, Some(dir.join(OsStr::from_bytes(&name))))
+}
+
+impl Backend for Fzf {
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::all_boxes();
+        capabilities.save_mode = true;
+        capabilities
+    }
+
+    fn can_run(&self) -> bool {
+        super::is_available("fzf") && super::has_terminal()
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let lines: Vec<String> = checklist.list.iter()
+            .map(|(tag, item, _)| format!("{}\t{}", tag, item))
+            .collect();
+        let args = vec!["--multi", "--delimiter", "\t"];
+        let (choice, lines) = get_lines(self.execute(&checklist.text, args, None, move |out| {
+            lines.iter().try_for_each(|line| writeln!(out, "{}", line))
+        })?)?;
+
+        let tags: Vec<String> = lines.iter()
+            .filter_map(|line| line.split('\t').next())
+            .map(ToString::to_string)
+            .collect();
+        match choice {
+            Choice::Yes => Ok((choice, Some(tags))),
+            _ => Ok((choice, None)),
+        }
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        let dir = match file_selection.path {
+            Some(ref path) if path.is_dir() => path.clone(),
+            _ => return Err(Error::InvalidConfig("path not valid".to_string())),
+        };
+        let save = file_selection.mode == FileSelectionMode::Save;
+        let mut args = vec!["--no-multi", "--read0", "--print0"];
+        if save {
+            // In save mode, the query is used as the file name if it does not match a file.
+            args.push("--print-query");
+        }
+
+        let walk_dir = dir.clone();
+        let output = self.execute(&file_selection.text, args, Some(&dir),
+            move |out| list_files(&walk_dir, out))?;
+        let (choice, lines) = get_raw_lines(output, b'\0')?;
+        Ok(selected_path(&dir, choice, lines, save))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.fallback.show_form(form)
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.fallback.show_gauge(gauge)
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.fallback.show_input(input)
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        let lines: Vec<String> = menu.list.chunks(2)
+            .map(|item| item.join("\t"))
+            .collect();
+        let args = vec!["--no-multi", "--delimiter", "\t"];
+        let (choice, lines) = get_lines(self.execute(&menu.text, args, None, move |out| {
+            lines.iter().try_for_each(|line| writeln!(out, "{}", line))
+        })?)?;
+
+        let tag = lines.first()
+            .and_then(|line| line.split('\t').next())
+            .map(ToString::to_string);
+        match choice {
+            Choice::Yes => Ok((choice, tag)),
+            _ => Ok((choice, None)),
+        }
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.fallback.show_message(message)
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.fallback.show_mixed_form(form)
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.fallback.show_mixed_gauge(gauge)
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.fallback.show_password(password)
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.fallback.show_password_form(form)
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.fallback.show_question(question)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    use super::*;
+
+    #[test]
+    fn list_files_lists_one_level_without_directories() {
+        let dir = std::env::temp_dir().join(format!("dialog-fzf-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("b.txt"), "").unwrap();
+        fs::write(dir.join("a.txt"), "").unwrap();
+        fs::write(dir.join(".hidden"), "").unwrap();
+        fs::write(dir.join("sub").join("nested.txt"), "").unwrap();
+
+        let mut out = Vec::new();
+        let result = list_files(&dir, &mut out);
+        fs::remove_dir_all(&dir).unwrap();
+
+        result.unwrap();
+        assert_eq!(out, b"a.txt\0b.txt\0");
+    }
+
+    #[test]
+    fn file_names_are_passed_as_bytes() {
+        let dir = std::env::temp_dir().join(format!("dialog-fzf-raw-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(OsStr::from_bytes(b"caf\xe9.txt")), "").unwrap();
+
+        let mut out = Vec::new();
+        let result = list_files(&dir, &mut out);
+        fs::remove_dir_all(&dir).unwrap();
+
+        result.unwrap();
+        assert_eq!(out, b"caf\xe9.txt\0");
+    }
+
+    #[test]
+    fn selected_path_keeps_raw_bytes() {
+        let output = |code: i32, stdout: &[u8]| process::Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.to_vec(),
+            stderr: Vec::new(),
+        };
+        let dir = Path::new("/tmp");
+        let (choice, lines) = get_raw_lines(output(0, b"caf\xe9.txt\0"), b'\0').unwrap();
+        let expected = dir.join(OsStr::from_bytes(b"caf\xe9.txt"));
+        assert_eq!(selected_path(dir, choice, lines, false), (Choice::Yes, Some(expected)));
+
+        let (choice, lines) = get_raw_lines(output(1, b"new\xff\0"), b'\0').unwrap();
+        let expected = dir.join(OsStr::from_bytes(b"new\xff"));
+        assert_eq!(selected_path(dir, choice, lines, true), (Choice::Yes, Some(expected)));
+
+        let (choice, lines) = get_raw_lines(output(1, b"\0"), b'\0').unwrap();
+        assert_eq!(selected_path(dir, choice, lines, true), (Choice::Cancel, None));
+        let (choice, lines) = get_raw_lines(output(130, b""), b'\0').unwrap();
+        assert_eq!(selected_path(dir, choice, lines, false), (Choice::Escape, None));
+    }
+
+    #[test]
+    fn exit_status_is_mapped_to_choice() {
+        let status = |code: i32| ExitStatus::from_raw(code << 8);
+        assert_eq!(get_choice(status(0)).unwrap(), Choice::Yes);
+        assert_eq!(get_choice(status(1)).unwrap(), Choice::Cancel);
+        assert_eq!(get_choice(status(130)).unwrap(), Choice::Escape);
+        assert!(get_choice(status(2)).is_err());
+    }
+}
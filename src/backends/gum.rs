@@ -0,0 +1,319 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::ffi::{OsStr, OsString};
+use std::io::Write;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::process;
+
+use crate::backends::{Backend, Capabilities, Stdio};
+use crate::{
+    Checklist, Choice, Error,
+    FileSelection, FileSelectionMode, Form,
+    FormField, Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Result
+};
+
+/// The `gum` backend.
+///
+/// This backend uses the subcommands of the external `gum` program to display dialog boxes in the
+/// terminal:
+/// - menus and checklists use `gum choose`,
+/// - inputs, passwords and forms use `gum input` (or `gum write` for multi-line inputs),
+/// - questions and messages use `gum confirm`,
+/// - file selections use `gum file`, or `gum input` in save mode.
+///
+/// gum has no progress bars, so gauges are displayed using the [`Stdio`][] backend.
+///
+/// [`Stdio`]: struct.Stdio.html
+#[derive(Debug, Default)]
+pub struct Gum {
+    multiline: bool,
+    fallback: Stdio,
+}
+
+impl Gum {
+    /// Creates a new `Gum` instance without configuration.
+    pub fn new() -> Gum {
+        Default::default()
+    }
+
+    /// Sets whether input dialog boxes accept multiple lines.
+    ///
+    /// Multi-line inputs are displayed using `gum write` instead of `gum input`.
+    pub fn set_multiline(mut self, multiline: bool) -> Gum {
+        self.multiline = multiline;
+        self
+    }
+
+    fn execute(&self, subcommand: &str, args: Vec<impl AsRef<OsStr>>, lines: Option<&[String]>)
+        -> Result<process::Output> {
+        let mut command = process::Command::new("gum");
+        command.arg(subcommand);
+        command.args(args);
+
+        // gum reads the options of gum choose from stdin, all other subcommands need the terminal
+        match lines {
+            Some(_) => command.stdin(process::Stdio::piped()),
+            None => command.stdin(process::Stdio::inherit()),
+        };
+        let mut child = command
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::inherit())
+            .spawn()
+            .map_err(|err| Error::from(("gum", err)))?;
+
+        if let Some(lines) = lines {
+            let mut stdin = child.stdin.take().ok_or("gum stdin not available")?;
+            for line in lines {
+                writeln!(stdin, "{}", line)?;
+            }
+        }
+        child.wait_with_output().map_err(Error::IoError)
+    }
+
+    fn show_fields(&self, text: &str, list: &[FormField], password: bool)
+        -> Result<(Choice, Option<String>)> {
+        let mut values: Vec<String> = Vec::new();
+        for (label, _, _, item, _, _, _, _) in list {
+            let prompt = format!("{} ", label);
+            let mut args = vec!["--header", text, "--prompt", &prompt];
+            // gum input reads the initial value from stdin if stdin is not a terminal.  Passwords
+            // are passed this way so that they do not show up in the process list.
+            let value = [item.clone()];
+            let mut lines = None;
+            if password {
+                args.push("--password");
+                if !item.is_empty() {
+                    lines = Some(&value[..]);
+                }
+            } else {
+                args.push("--value");
+                args.push(item);
+            }
+            match get_choices(self.execute("input", args, lines)?)? {
+                (Choice::Yes, Some(value)) => values.push(value),
+                (choice, _) => return Ok((choice, None)),
+            }
+        }
+        Ok((Choice::Yes, Some(values.join("\n"))))
+    }
+}
+
+impl AsRef<Gum> for Gum {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+// Maps gum's exit status to a choice.  gum exits with 1 if the user declined and with 130 if the
+// user aborted the dialog.
+fn get_choice(status: process::ExitStatus) -> Result<Choice> {
+    match status.code() {
+        Some(0) => Ok(Choice::Yes),
+        Some(1) => Ok(Choice::No),
+        Some(130) => Ok(Choice::Escape),
+        _ => Err(Error::from(("gum", status))),
+    }
+}
+
+fn get_choices(output: process::Output) -> Result<(Choice, Option<String>)> {
+    let (choice, value) = get_raw_choices(output)?;
+    Ok((choice, value.map(String::from_utf8).transpose()?))
+}
+
+// Like get_choices, but keeps the value as bytes, as file names might not be valid UTF-8.
+fn get_raw_choices(output: process::Output) -> Result<(Choice, Option<Vec<u8>>)> {
+    match get_choice(output.status)? {
+        Choice::Yes => {
+            let mut stdout = output.stdout;
+            while stdout.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+                stdout.pop();
+            }
+            Ok((Choice::Yes, Some(stdout)))
+        }
+        Choice::No => Ok((Choice::Cancel, None)),
+        choice => Ok((choice, None)),
+    }
+}
+
+// Maps the selected lines printed by gum choose to the tags of the checklist items.  gum prints
+// one selected line per line.
+fn selected_tags(output: &str, lines: &[String], list: &[(String, String, bool)]) -> Vec<String> {
+    output.lines()
+        .filter_map(|line| lines.iter().position(|l| l == line))
+        .map(|index| list[index].0.clone())
+        .collect()
+}
+
+impl Backend for Gum {
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::all_boxes();
+        capabilities.save_mode = true;
+        capabilities
+    }
+
+    fn can_run(&self) -> bool {
+        super::is_available("gum") && super::has_terminal()
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let lines: Vec<String> = checklist.list.iter()
+            .map(|(tag, item, _)| format!("{}  {}", tag, item))
+            .collect();
+        let selected: Vec<&str> = checklist.list.iter()
+            .zip(&lines)
+            .filter(|((_, _, status), _)| *status)
+            .map(|(_, line)| line.as_str())
+            .collect();
+        let mut args = vec!["--no-limit", "--header", &checklist.text];
+        // gum splits the value of --selected at commas, so pass every item separately
+        for line in selected {
+            args.push("--selected");
+            args.push(line);
+        }
+
+        let (choice, output) = get_choices(self.execute("choose", args, Some(&lines))?)?;
+        let tags = output.map(|output| selected_tags(&output, &lines, &checklist.list));
+        Ok((choice, tags))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        // The directory and the selected path might not be valid UTF-8.
+        let dir = crate::dir_to_os_string(&file_selection.path)?;
+        let output = match file_selection.mode {
+            FileSelectionMode::Open => self.execute("file", vec![dir.as_os_str()], None)?,
+            FileSelectionMode::Save => {
+                let text = OsStr::new(&file_selection.text);
+                let args = vec![OsStr::new("--header"), text, OsStr::new("--value"), &dir];
+                self.execute("input", args, None)?
+            }
+        };
+        let (choice, path) = get_raw_choices(output)?;
+        Ok((choice, path.map(|path| PathBuf::from(OsString::from_vec(path)))))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.show_fields(&form.text, &form.list, false)
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.fallback.show_gauge(gauge)
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        let mut args = vec!["--header", &input.text];
+        if let Some(ref default) = input.default {
+            args.push("--value");
+            args.push(default);
+        }
+        let subcommand = if self.multiline { "write" } else { "input" };
+        get_choices(self.execute(subcommand, args, None)?)
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        let lines: Vec<String> = menu.list.chunks(2)
+            .map(|item| item.join("  "))
+            .collect();
+        let args = vec!["--limit", "1", "--header", &menu.text];
+        let (choice, output) = get_choices(self.execute("choose", args, Some(&lines))?)?;
+        let tag = output
+            .and_then(|output| lines.iter().position(|l| *l == output))
+            .map(|index| menu.list[index * 2].clone());
+        Ok((choice, tag))
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        let args = vec!["--affirmative", "OK", "--negative", "", &message.text];
+        let status = self.execute("confirm", args, None)?.status;
+        get_choice(status).map(|_| ())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.show_fields(&form.text, &form.list, false)
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.fallback.show_mixed_gauge(gauge)
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        let args = vec!["--password", "--header", &password.text];
+        get_choices(self.execute("input", args, None)?)
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.show_fields(&form.text, &form.list, true)
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        let status = self.execute("confirm", vec![&question.text], None)?.status;
+        get_choice(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{ExitStatus, Output};
+
+    use super::*;
+
+    fn output(code: i32, stdout: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn exit_status_is_mapped_to_choice() {
+        let status = |code: i32| ExitStatus::from_raw(code << 8);
+        assert_eq!(get_choice(status(0)).unwrap(), Choice::Yes);
+        assert_eq!(get_choice(status(1)).unwrap(), Choice::No);
+        assert_eq!(get_choice(status(130)).unwrap(), Choice::Escape);
+        assert!(get_choice(status(2)).is_err());
+        assert!(get_choice(ExitStatus::from_raw(9)).is_err());
+    }
+
+    #[test]
+    fn output_is_mapped_to_answer() {
+        let answer = get_choices(output(0, "value\n")).unwrap();
+        assert_eq!(answer, (Choice::Yes, Some("value".to_string())));
+        assert_eq!(get_choices(output(1, "")).unwrap(), (Choice::Cancel, None));
+        assert_eq!(get_choices(output(130, "")).unwrap(), (Choice::Escape, None));
+        assert!(get_choices(output(2, "")).is_err());
+    }
+
+    #[test]
+    fn raw_output_is_kept() {
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: b"/tmp/caf\xe9.txt\n".to_vec(),
+            stderr: Vec::new(),
+        };
+        let answer = get_raw_choices(output).unwrap();
+        assert_eq!(answer, (Choice::Yes, Some(b"/tmp/caf\xe9.txt".to_vec())));
+    }
+
+    #[test]
+    fn multiple_selections_are_split() {
+        let list = vec![
+            ("a".to_string(), "first, item".to_string(), false),
+            ("b".to_string(), "second item".to_string(), true),
+            ("c".to_string(), "third item".to_string(), false),
+        ];
+        let lines: Vec<String> = list.iter()
+            .map(|(tag, item, _)| format!("{}  {}", tag, item))
+            .collect();
+        let tags = selected_tags("a  first, item\nc  third item\n", &lines, &list);
+        assert_eq!(tags, vec!["a".to_string(), "c".to_string()]);
+        assert!(selected_tags("unknown\n", &lines, &list).is_empty());
+        assert!(selected_tags("", &lines, &list).is_empty());
+    }
+}
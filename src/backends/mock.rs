@@ -0,0 +1,231 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::backends::Capabilities;
+use crate::{
+    Checklist, Choice, Error,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Request, Response, Result
+};
+
+/// The `mock` backend.
+///
+/// This backend does not display anything.  Instead, it records every dialog box that it was
+/// asked to show and answers it with the next response from a queue.  It is intended for testing
+/// code that displays dialog boxes without a terminal.
+///
+/// Messages and gauges are recorded but do not consume a response.  For checklists, the value of
+/// the response contains one selected tag per line.  If the queue is empty when a response is
+/// needed, an error is returned.
+///
+/// # Example
+///
+/// ```
+/// use dialog::{Choice, DialogBox, Request};
+///
+/// let backend = dialog::backends::Mock::new();
+/// backend.push_response((Choice::Yes, Some("foo")));
+///
+/// let name = dialog::Input::new("Please enter your name")
+///     .show_with(&backend)
+///     .expect("Could not display dialog box");
+/// assert_eq!(name, (Choice::Yes, Some("foo".to_string())));
+///
+/// let shown = backend.shown();
+/// assert_eq!(shown.len(), 1);
+/// assert_eq!(shown[0].text(), "Please enter your name");
+/// assert_eq!(shown[0], Request::Input {
+///     text: "Please enter your name".to_string(),
+///     default: None,
+///     id: None,
+///     timeout: None,
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct Mock {
+    responses: Mutex<VecDeque<Response>>,
+    shown: Mutex<Vec<Request>>,
+    capabilities: Capabilities,
+}
+
+impl Mock {
+    /// Creates a new `Mock` instance without queued responses.
+    pub fn new() -> Mock {
+        Default::default()
+    }
+
+    /// Sets the capabilities reported by this backend.
+    ///
+    /// Per default, the backend reports support for all dialog box types and no optional
+    /// features.  The capabilities are only reported, the backend still answers all dialog
+    /// boxes.
+    pub fn set_capabilities(mut self, capabilities: Capabilities) -> Mock {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Adds a response to the end of the queue.
+    pub fn push_response(&self, response: impl Into<Response>) {
+        lock(&self.responses).push_back(response.into());
+    }
+
+    /// Returns all dialog boxes shown with this backend, in the order they were shown.
+    pub fn shown(&self) -> Vec<Request> {
+        lock(&self.shown).clone()
+    }
+
+    /// Returns the number of responses that have not been used yet.
+    pub fn remaining(&self) -> usize {
+        lock(&self.responses).len()
+    }
+
+    fn record(&self, request: Request) {
+        lock(&self.shown).push(request);
+    }
+
+    fn respond(&self, request: Request) -> Result<Response> {
+        let response = lock(&self.responses).pop_front();
+        let response = response.ok_or_else(|| {
+            Error::Error(format!("no response queued for {:?} dialog", request.kind()))
+        });
+        self.record(request);
+        response
+    }
+
+    fn respond_choices(&self, request: Request) -> Result<(Choice, Option<String>)> {
+        self.respond(request).map(|response| (response.choice, response.value))
+    }
+}
+
+// The mock only stores plain data, so a panic while the lock was held cannot leave it in an
+// inconsistent state.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl AsRef<Mock> for Mock {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl super::Backend for Mock {
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities.clone()
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let (choice, value) = self.respond_choices(checklist.into())?;
+        let tags = value.map(|value| value.lines().map(ToString::to_string).collect());
+        Ok((choice, tags))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.respond_choices(file_selection.into())
+            .map(|(choice, path)| (choice, path.map(PathBuf::from)))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.respond_choices(form.into())
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.record(gauge.into());
+        Ok(())
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.respond_choices(input.into())
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        self.respond_choices(menu.into())
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.record(message.into());
+        Ok(())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.respond_choices(form.into())
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.record(gauge.into());
+        Ok(())
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.respond_choices(password.into())
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.respond_choices(form.into())
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.respond(question.into()).map(|response| response.choice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::DialogBox;
+
+    #[test]
+    fn responses_are_used_in_order() {
+        let backend = Mock::new();
+        backend.push_response((Choice::Yes, Some("first")));
+        backend.push_response(Choice::No);
+        assert_eq!(backend.remaining(), 2);
+
+        let input = Input::new("Name").show_with(&backend).unwrap();
+        assert_eq!(input, (Choice::Yes, Some("first".to_string())));
+        let question = Question::new("Continue?").show_with(&backend).unwrap();
+        assert_eq!(question, Choice::No);
+        assert_eq!(backend.remaining(), 0);
+
+        let kinds: Vec<_> = backend.shown().iter().map(Request::kind).collect();
+        assert_eq!(kinds, [crate::BoxKind::Input, crate::BoxKind::Question]);
+    }
+
+    #[test]
+    fn messages_do_not_use_responses() {
+        let backend = Mock::new();
+        backend.push_response(Choice::Yes);
+        Message::new("Hello").show_with(&backend).unwrap();
+        assert_eq!(backend.remaining(), 1);
+        assert_eq!(backend.shown().len(), 1);
+    }
+
+    #[test]
+    fn exhausted_queue_returns_error() {
+        let backend = Mock::new();
+        let result = Question::new("Continue?").show_with(&backend);
+        assert!(matches!(result, Err(Error::Error(_))));
+        // The dialog box is recorded even if there was no response for it.
+        assert_eq!(backend.shown().len(), 1);
+    }
+
+    #[test]
+    fn mock_can_be_shared_between_threads() {
+        let backend = Mock::new();
+        backend.push_response(Choice::Yes);
+        thread::scope(|scope| {
+            scope.spawn(|| Question::new("Continue?").show_with(&backend).unwrap());
+        });
+        assert_eq!(backend.remaining(), 0);
+    }
+}
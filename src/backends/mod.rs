@@ -1,14 +1,49 @@
 // Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
 // SPDX-License-Identifier: MIT
 
+mod broker;
+mod capabilities;
 mod dialog;
+mod fallback;
+mod frontend;
+mod fzf;
+mod gum;
+mod mock;
+mod preseed;
+mod record;
+mod rofi;
+mod running;
+mod script;
+mod stdio;
+mod web;
 
-pub use crate::backends::dialog::Dialog;
+pub use crate::backends::broker::{default_broker_path, Broker, BrokerServer};
+pub use crate::backends::capabilities::Capabilities;
+pub use crate::backends::dialog::{CommandLine, Dialog};
+pub use crate::backends::fallback::Fallback;
+pub use crate::backends::frontend::{Frontend, Reply};
+pub use crate::backends::fzf::Fzf;
+pub use crate::backends::gum::Gum;
+pub use crate::backends::mock::Mock;
+pub use crate::backends::preseed::Preseed;
+pub use crate::backends::record::{Record, Replay};
+pub use crate::backends::rofi::Rofi;
+pub use crate::backends::running::{Closer, Running};
+pub use crate::backends::script::Script;
+pub use crate::backends::stdio::Stdio;
+pub use crate::backends::web::Web;
 
-// use std::env;
-// use std::path;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+#[cfg(feature = "tokio")]
+use std::future::Future;
+use std::os::unix::fs::PermissionsExt;
+use std::path;
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// A dialog backend.
 ///
@@ -20,8 +55,41 @@ use crate::Result;
 /// [`default_backend`]: ../fn.default_backend.html
 /// [`show_with`]: ../trait.DialogBox.html#method.show_with
 pub trait Backend {
+    /// Returns the features supported by this backend.
+    ///
+    /// The default implementation reports no supported dialog box types and no optional
+    /// features, so backends have to list the dialog box types they support.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::with_boxes(&[])
+    }
+
+    /// Checks whether the backend can be used in the current environment, for example whether
+    /// its executable is installed and a terminal or display is available.
+    ///
+    /// The default implementation returns `true`.
+    fn can_run(&self) -> bool {
+        true
+    }
+
+    /// Starts displaying the given dialog box and returns without waiting for the answer.
+    ///
+    /// The returned [`Running`][] instance can be used to wait for the answer or to close the
+    /// dialog box.  The default implementation returns an [`Error::Unsupported`][] error.
+    ///
+    /// [`Running`]: struct.Running.html
+    /// [`Error::Unsupported`]: ../enum.Error.html#variant.Unsupported
+    fn spawn(&self, request: super::Request) -> Result<Running> {
+        Err(super::Error::Unsupported(format!(
+            "{:?} dialog boxes cannot be displayed in the background",
+            request.kind()
+        )))
+    }
+
+    /// Shows the given checklist dialog and returns the button choice and the selected tags.
+    fn show_checklist(&self, checklist: &super::Checklist) -> Result<(super::Choice, Option<Vec<String>>)>;
+
     /// Shows the given file selection dialog and returns the button choice and file name selection.
-    fn show_file_selection(&self, file_selection: &super::FileSelection) -> Result<(super::Choice, Option<String>)>;
+    fn show_file_selection(&self, file_selection: &super::FileSelection) -> Result<(super::Choice, Option<path::PathBuf>)>;
 
     /// Shows a form of labels and text fields and returns the button choice and inputs.
     fn show_form(&self, form: &super::Form) -> Result<(super::Choice, Option<String>)>;
@@ -54,22 +122,218 @@ pub trait Backend {
     fn show_question(&self, question: &super::Question) -> Result<super::Choice>;
 }
 
-/*
+/// A boxed future as returned by [`AsyncBackend`][].
+///
+/// [`AsyncBackend`]: trait.AsyncBackend.html
+#[cfg(feature = "tokio")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A dialog backend that can display dialog boxes without blocking an async executor.
+///
+/// This trait is only available if the `tokio` feature is enabled.  It is currently implemented
+/// by the [`Dialog`][] backend.  Use the [`show_with_async`][] method of a dialog box to show it
+/// with an async backend.  If the returned future is dropped before it completes, the dialog box
+/// is closed.
+///
+/// [`Dialog`]: struct.Dialog.html
+/// [`show_with_async`]: ../trait.AsyncDialogBox.html#tymethod.show_with_async
+#[cfg(feature = "tokio")]
+pub trait AsyncBackend {
+    /// Shows the described dialog box and returns the answer.
+    ///
+    /// The answer has the same format as the answer returned by [`Request::show_with`][].
+    ///
+    /// [`Request::show_with`]: ../enum.Request.html#method.show_with
+    fn show_request(&self, request: super::Request) -> BoxFuture<'_, Result<super::Response>>;
+
+    /// Shows the given file selection and returns the button choice and the selected path.
+    ///
+    /// The default implementation uses [`show_request`][], so paths that are not valid UTF-8 are
+    /// converted lossily.
+    ///
+    /// [`show_request`]: #tymethod.show_request
+    fn show_file_selection(&self, file_selection: &super::FileSelection)
+        -> BoxFuture<'_, Result<(super::Choice, Option<path::PathBuf>)>> {
+        let response = self.show_request(file_selection.into());
+        Box::pin(async move { response.await.map(crate::request::file_selection_output) })
+    }
+}
+
+// The names of the backends that can be selected with the DIALOG environment variable.
+const NAMES: &[&str] = &["broker", "dialog", "dmenu", "fzf", "gum", "preseed", "rofi", "stdio",
+    "web", "whiptail"];
+
+// The backends that are tried in this order if DIALOG is not set and the process has a terminal.
+const TERMINAL_NAMES: &[&str] = &["dialog", "whiptail", "stdio"];
+
+// The backends that are tried in this order if DIALOG is not set and the process has no terminal.
+const GRAPHICAL_NAMES: &[&str] = &["rofi", "dmenu", "stdio"];
+
+// Checks whether an executable with the given name exists in one of the directories in PATH.
 pub(crate) fn is_available(name: &str) -> bool {
-    if let Ok(path) = env::var("PATH") {
-        for part in path.split(':') {
-            if path::Path::new(part).join(name).exists() {
-                return true;
+    if let Some(path) = env::var_os("PATH") {
+        for part in env::split_paths(&path) {
+            let file = path::Path::new(&part).join(name);
+            if let Ok(metadata) = fs::metadata(&file) {
+                if metadata.is_file() && metadata.permissions().mode() & 0o111 != 0 {
+                    return true;
+                }
             }
         }
     }
     false
 }
-*/
 
-pub(crate) fn from_str(s: &str) -> Option<Box<dyn Backend>> {
+// Checks whether the process has a controlling terminal that supports curses applications.
+pub(crate) fn has_terminal() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    !term.is_empty() && term != "dumb" && fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .is_ok()
+}
+
+// Creates the backends from a colon-separated list of names as used in the DIALOG environment
+// variable.  Backends that cannot be used in the current environment are skipped.  If none of
+// the backends can be used, an error is returned.  If the list is empty, None is returned.
+pub(crate) fn from_env(value: &str) -> Result<Option<Box<dyn Backend>>> {
+    let names: Vec<String> = value.split(':')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let mut backends: Vec<Box<dyn Backend>> = Vec::new();
+    for name in &names {
+        if !NAMES.contains(&name.as_str()) {
+            return Err(Error::InvalidConfig(format!(
+                "unknown backend '{}' in DIALOG, valid backends are: {}", name, NAMES.join(", ")
+            )));
+        }
+        let backend = from_str(name)?;
+        if backend.can_run() {
+            backends.push(backend);
+        }
+    }
+
+    match backends.len() {
+        0 if names.is_empty() => Ok(None),
+        0 => Err(Error::NotFound(format!(
+            "none of the backends in DIALOG can be used: {}", names.join(", ")
+        ))),
+        1 => Ok(backends.pop()),
+        _ => Ok(Some(Box::new(Fallback::new(backends)))),
+    }
+}
+
+// Creates a backend from the backends that can be used in the current environment.  On a
+// terminal, plain prompts are preferred over graphical launchers.
+pub(crate) fn detect() -> Result<Box<dyn Backend>> {
+    let names = if has_terminal() { TERMINAL_NAMES } else { GRAPHICAL_NAMES };
+    let mut backends = Vec::new();
+    for name in names {
+        let backend = from_str(name)?;
+        if backend.can_run() {
+            backends.push(backend);
+        }
+    }
+    match backends.len() {
+        0 => Err(Error::NotFound(format!(
+            "none of the backends can be used: {}", names.join(", ")
+        ))),
+        1 => Ok(backends.remove(0)),
+        _ => Ok(Box::new(Fallback::new(backends))),
+    }
+}
+
+// Creates the preseed backend from the file in DIALOG_PRESEED, if set.
+fn preseed(path: Option<OsString>) -> Result<Preseed> {
+    match path {
+        Some(path) => Preseed::open(&path).map_err(|err| {
+            Error::InvalidConfig(format!("could not read the preseed file {} from DIALOG_PRESEED: {}",
+                path::Path::new(&path).display(), err))
+        }),
+        None => Ok(Preseed::new()),
+    }
+}
+
+pub(crate) fn from_str(s: &str) -> Result<Box<dyn Backend>> {
     match s.to_lowercase().as_ref() {
-        "dialog" => Some(Box::new(Dialog::new())),
-        _ => None,
+        "broker" => Ok(Box::new(Broker::default())),
+        "dialog" => Ok(Box::new(Dialog::new())),
+        "dmenu" => Ok(Box::new(Rofi::dmenu())),
+        "fzf" => Ok(Box::new(Fzf::new())),
+        "gum" => Ok(Box::new(Gum::new())),
+        "preseed" => Ok(Box::new(preseed(env::var_os("DIALOG_PRESEED"))?)),
+        "rofi" => Ok(Box::new(Rofi::new())),
+        "stdio" => Ok(Box::new(Stdio::new())),
+        "web" => {
+            let port = match env::var("DIALOG_WEB_PORT") {
+                Ok(port) => port.parse().map_err(|_| {
+                    Error::InvalidConfig(format!("invalid port '{}' in DIALOG_WEB_PORT", port))
+                })?,
+                Err(_) => 0,
+            };
+            let web = Web::bind(port)?.set_url_handler(|url| {
+                eprintln!("Open {} in a web browser to answer the dialog", url)
+            });
+            Ok(Box::new(web))
+        }
+        "whiptail" => Ok(Box::new(Dialog::whiptail())),
+        _ => Err(Error::InvalidConfig(format!("unknown backend '{}'", s))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_backend_names_are_rejected() {
+        assert!(matches!(from_env("whiptail:dialog:stdio"), Ok(Some(_))));
+        let result = from_env("newt:dialog:stdio");
+        assert!(matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("newt")));
+    }
+
+    #[test]
+    fn unusable_backends_are_reported() {
+        let result = from_env("rofi:broker");
+        assert!(matches!(result, Err(Error::NotFound(ref msg)) if msg.contains("rofi, broker")));
+        assert!(matches!(from_env(" : "), Ok(None)));
+    }
+
+    #[test]
+    fn backend_names_are_case_insensitive() {
+        assert!(matches!(from_env(" Stdio "), Ok(Some(_))));
+        assert!(matches!(from_env("preseed:STDIO"), Ok(Some(_))));
+    }
+
+    #[test]
+    fn fallback_capabilities_skip_unusable_backends() {
+        let mut capabilities = Capabilities::with_boxes(&[crate::BoxKind::Menu]);
+        capabilities.timeout = true;
+        let fallback = Fallback::new(vec![
+            Box::new(Broker::new("/nonexistent/dialog-broker.sock")),
+            Box::new(Mock::new().set_capabilities(capabilities.clone())),
+        ]);
+        assert!(fallback.can_run());
+        assert_eq!(fallback.capabilities(), capabilities);
+
+        let fallback = Fallback::new(vec![Box::new(Broker::new("/nonexistent/dialog-broker.sock"))]);
+        assert!(!fallback.can_run());
+        assert!(fallback.capabilities().boxes.is_empty());
+    }
+
+    #[test]
+    fn preseed_errors_are_returned() {
+        let result = preseed(Some("/nonexistent/preseed.toml".into()));
+        assert!(matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("/nonexistent")));
+        assert!(preseed(None).is_ok());
+    }
+
+    #[test]
+    fn fallback_can_be_debugged() {
+        let fallback = Fallback::new(vec![Box::new(Stdio::new()), Box::new(Mock::new())]);
+        assert!(format!("{:?}", fallback).starts_with("Fallback { backends: [Capabilities {"));
     }
 }
@@ -0,0 +1,394 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+use std::env;
+use std::error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::backends::{Backend, Capabilities};
+use crate::{
+    BoxKind, Checklist, Choice, Error,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Response, Result
+};
+
+// A preseeded answer.  Plain values are answers with the yes/ok button.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum Answer {
+    Bool(bool),
+    Text(String),
+    List(Vec<String>),
+    Response(Response),
+}
+
+/// The `preseed` backend.
+///
+/// This backend does not display anything.  Instead, it answers every dialog box from a preseed
+/// file or from environment variables, which makes it possible to run interactive programs
+/// unattended.  Dialog boxes are identified by the id set with the `id` method of the dialog box,
+/// for example [`Input::id`][].
+///
+/// For a dialog box with the id `id`, the environment variable `DIALOG_ANSWER_<ID>` is checked
+/// first, where `<ID>` is the id in upper case with all characters except ASCII letters and
+/// digits replaced by underscores.  If it is not set, the answer is read from the preseed file.
+/// If there is no answer, an [`Error::NoAnswer`][] error is returned, and if the dialog box does
+/// not have an id, an [`Error::InvalidConfig`][] error.  Messages and gauges do not need an
+/// answer.  Like `dialog`, form answers contain one value per field, each followed by a newline.
+///
+/// The preseed file maps the ids to the answers, either as a TOML file (with the extension
+/// `.toml`) or as a JSON object.  The answer can be:
+/// - a string: the input, password, selected menu tag or file name, or `yes` or `no` for a
+///   question,
+/// - a boolean for a question,
+/// - a list of strings: the selected checklist tags or the values of the form fields,
+/// - a table with the `choice` and the `value` fields to choose another button, see
+///   [`Response`][].
+///
+/// In environment variables, checklist tags are separated by whitespace and form values by
+/// newlines.  If the backend is selected by setting the `DIALOG` environment variable to
+/// `preseed`, the preseed file is read from the path in the `DIALOG_PRESEED` environment variable.
+///
+/// ```toml
+/// hostname = "example.org"
+/// continue = true
+/// packages = ["vim", "git"]
+///
+/// [user]
+/// choice = "cancel"
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use dialog::{Choice, DialogBox};
+///
+/// let backend = dialog::backends::Preseed::from_json(r#"{"continue": "yes"}"#)
+///     .expect("Could not read answers");
+/// let choice = dialog::Question::new("Do you want to continue?")
+///     .id("continue")
+///     .show_with(&backend)
+///     .expect("Could not answer dialog box");
+/// assert_eq!(choice, Choice::Yes);
+/// ```
+///
+/// [`Error::InvalidConfig`]: ../enum.Error.html#variant.InvalidConfig
+/// [`Error::NoAnswer`]: ../enum.Error.html#variant.NoAnswer
+/// [`Input::id`]: ../struct.Input.html#method.id
+/// [`Response`]: ../struct.Response.html
+#[derive(Debug, Default)]
+pub struct Preseed {
+    answers: HashMap<String, Answer>,
+}
+
+impl Preseed {
+    /// Creates a new `Preseed` instance that only reads answers from environment variables.
+    pub fn new() -> Preseed {
+        Default::default()
+    }
+
+    /// Creates a new `Preseed` instance that reads answers from the given preseed file.
+    ///
+    /// If the file name has the extension `.toml`, the file is parsed as TOML, otherwise as JSON.
+    pub fn open(path: impl AsRef<Path>) -> Result<Preseed> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        if path.extension() == Some("toml".as_ref()) {
+            Preseed::from_toml(&content)
+        } else {
+            Preseed::from_json(&content)
+        }
+    }
+
+    /// Creates a new `Preseed` instance with the answers from the given JSON object.
+    pub fn from_json(s: &str) -> Result<Preseed> {
+        let answers = serde_json::from_str(s).map_err(parse_error)?;
+        Ok(Preseed { answers })
+    }
+
+    /// Creates a new `Preseed` instance with the answers from the given TOML document.
+    pub fn from_toml(s: &str) -> Result<Preseed> {
+        let answers = toml::from_str(s).map_err(parse_error)?;
+        Ok(Preseed { answers })
+    }
+
+    fn answer(&self, id: &Option<String>, kind: BoxKind) -> Result<(String, Answer)> {
+        let id = id.as_ref().ok_or_else(|| {
+            Error::InvalidConfig(format!("cannot preseed {:?} dialog without an id", kind))
+        })?;
+        let key = env_key(id);
+        if let Ok(value) = env::var(&key) {
+            return Ok((id.clone(), Answer::Text(value)));
+        }
+        match self.answers.get(id) {
+            Some(answer) => Ok((id.clone(), answer.clone())),
+            None => Err(Error::NoAnswer(format!(
+                "no preseeded answer for {:?} dialog '{}', set {} or add it to the preseed file",
+                kind, id, key
+            ))),
+        }
+    }
+
+    fn answer_value(&self, id: &Option<String>, kind: BoxKind) -> Result<(Choice, Option<String>)> {
+        let response = match self.answer(id, kind)? {
+            (_, Answer::Text(text)) => Response::new(Choice::Yes, Some(text)),
+            (_, Answer::List(values)) => Response::new(Choice::Yes, Some(values.join("\n"))),
+            (_, Answer::Response(response)) => response,
+            (id, Answer::Bool(_)) => return Err(invalid_answer(&id, "a string")),
+        };
+        Ok((response.choice, response.value))
+    }
+
+    // Returns the answer for a form.  Like dialog, every value is followed by a newline.
+    fn answer_form(&self, id: &Option<String>, kind: BoxKind) -> Result<(Choice, Option<String>)> {
+        let response = match self.answer(id, kind)? {
+            (_, Answer::Text(text)) => Response::new(Choice::Yes, Some(text)),
+            (_, Answer::List(values)) => {
+                let value = values.iter().map(|value| format!("{}\n", value)).collect();
+                return Ok((Choice::Yes, Some(value)));
+            }
+            (_, Answer::Response(response)) => response,
+            (id, Answer::Bool(_)) => return Err(invalid_answer(&id, "a list of values")),
+        };
+        let value = response.value.map(|mut value| {
+            if !value.ends_with('\n') {
+                value.push('\n');
+            }
+            value
+        });
+        Ok((response.choice, value))
+    }
+}
+
+impl AsRef<Preseed> for Preseed {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+// Returns the name of the environment variable with the answer for the given id.
+fn env_key(id: &str) -> String {
+    let id: String = id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("DIALOG_ANSWER_{}", id)
+}
+
+fn parse_error(err: impl error::Error + Send + Sync + 'static) -> Error {
+    Error::ParseError("could not parse preseed answers".to_string(), Box::new(err))
+}
+
+fn invalid_answer(id: &str, expected: &str) -> Error {
+    Error::InvalidConfig(format!("invalid preseeded answer for dialog '{}': expected {}", id,
+        expected))
+}
+
+impl Backend for Preseed {
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::all_boxes();
+        capabilities.save_mode = true;
+        capabilities
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let (id, answer) = self.answer(&checklist.id, BoxKind::Checklist)?;
+        let (choice, tags) = match answer {
+            Answer::List(tags) => (Choice::Yes, Some(tags)),
+            Answer::Text(text) => {
+                (Choice::Yes, Some(text.split_whitespace().map(ToString::to_string).collect()))
+            }
+            Answer::Response(response) => {
+                let tags = response.value.map(|value| {
+                    value.lines().map(ToString::to_string).collect()
+                });
+                (response.choice, tags)
+            }
+            Answer::Bool(_) => return Err(invalid_answer(&id, "a list of tags")),
+        };
+        if let Some(ref tags) = tags {
+            let unknown = tags.iter().find(|tag| !checklist.list.iter().any(|(t, _, _)| t == *tag));
+            if let Some(tag) = unknown {
+                return Err(invalid_answer(&id, &format!("a checklist tag, got '{}'", tag)));
+            }
+        }
+        Ok((choice, tags))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.answer_value(&file_selection.id, BoxKind::FileSelection)
+            .map(|(choice, path)| (choice, path.map(PathBuf::from)))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.answer_form(&form.id, BoxKind::Form)
+    }
+
+    fn show_gauge(&self, _gauge: &Gauge) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.answer_value(&input.id, BoxKind::Input)
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        let (choice, tag) = self.answer_value(&menu.id, BoxKind::Menu)?;
+        if let Some(ref tag) = tag {
+            if !menu.list.chunks(2).any(|item| item[0] == *tag) {
+                let id = menu.id.as_deref().unwrap_or_default();
+                return Err(invalid_answer(id, &format!("a menu tag, got '{}'", tag)));
+            }
+        }
+        Ok((choice, tag))
+    }
+
+    fn show_message(&self, _message: &Message) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.answer_form(&form.id, BoxKind::MixedForm)
+    }
+
+    fn show_mixed_gauge(&self, _gauge: &MixedGauge) -> Result<()> {
+        Ok(())
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.answer_value(&password.id, BoxKind::Password)
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.answer_form(&form.id, BoxKind::PasswordForm)
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        match self.answer(&question.id, BoxKind::Question)? {
+            (_, Answer::Bool(true)) => Ok(Choice::Yes),
+            (_, Answer::Bool(false)) => Ok(Choice::No),
+            (_, Answer::Response(response)) => Ok(response.choice),
+            (id, Answer::Text(text)) => match text.to_lowercase().as_ref() {
+                "y" | "yes" | "true" => Ok(Choice::Yes),
+                "n" | "no" | "false" => Ok(Choice::No),
+                _ => Err(invalid_answer(&id, "yes or no")),
+            },
+            (id, Answer::List(_)) => Err(invalid_answer(&id, "yes or no")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DialogBox;
+
+    const TOML: &str = r#"
+        host = "example.org"
+        continue = true
+        packages = ["vim", "git"]
+        account = ["jane", "s3cret"]
+
+        [user]
+        choice = "cancel"
+    "#;
+
+    fn items(tags: &[&str]) -> Vec<(String, String, bool)> {
+        tags.iter().map(|tag| (tag.to_string(), String::new(), false)).collect()
+    }
+
+    fn menu(tags: &[&str]) -> Vec<[String; 2]> {
+        tags.iter().map(|tag| [tag.to_string(), String::new()]).collect()
+    }
+
+    #[test]
+    fn answers_are_parsed_from_toml() {
+        let backend = Preseed::from_toml(TOML).unwrap();
+        let answer = Input::new("Host").id("host").show_with(&backend).unwrap();
+        assert_eq!(answer, (Choice::Yes, Some("example.org".to_string())));
+        let choice = Question::new("Continue?").id("continue").show_with(&backend).unwrap();
+        assert_eq!(choice, Choice::Yes);
+        let checklist = Checklist::new("Packages", 3, items(&["vim", "git", "emacs"]))
+            .id("packages")
+            .show_with(&backend)
+            .unwrap();
+        assert_eq!(checklist, (Choice::Yes, Some(vec!["vim".to_string(), "git".to_string()])));
+        let answer = Input::new("User").id("user").show_with(&backend).unwrap();
+        assert_eq!(answer, (Choice::Cancel, None));
+    }
+
+    #[test]
+    fn answers_are_parsed_from_json() {
+        let json = r#"{
+            "continue": "no",
+            "shell": "zsh",
+            "user": {"choice": "yes", "value": "jane"}
+        }"#;
+        let backend = Preseed::from_json(json).unwrap();
+        let choice = Question::new("Continue?").id("continue").show_with(&backend).unwrap();
+        assert_eq!(choice, Choice::No);
+        let answer = Menu::new("Shell", 2, menu(&["bash", "zsh"])).id("shell").show_with(&backend);
+        assert_eq!(answer.unwrap(), (Choice::Yes, Some("zsh".to_string())));
+        let answer = Input::new("User").id("user").show_with(&backend).unwrap();
+        assert_eq!(answer, (Choice::Yes, Some("jane".to_string())));
+        let err = Preseed::from_json("[1, 2]").unwrap_err();
+        assert!(matches!(err, Error::ParseError(..)));
+        assert!(error::Error::source(&err).is_some_and(|source| source.is::<serde_json::Error>()));
+        let err = Preseed::from_toml("host = ").unwrap_err();
+        assert!(error::Error::source(&err).is_some_and(|source| source.is::<toml::de::Error>()));
+    }
+
+    #[test]
+    fn environment_overrides_file() {
+        let backend = Preseed::from_json(r#"{"preseed-test.env": "file"}"#).unwrap();
+        env::set_var("DIALOG_ANSWER_PRESEED_TEST_ENV", "environment");
+        let answer = Input::new("Value").id("preseed-test.env").show_with(&backend);
+        env::remove_var("DIALOG_ANSWER_PRESEED_TEST_ENV");
+        assert_eq!(answer.unwrap(), (Choice::Yes, Some("environment".to_string())));
+        let answer = Input::new("Value").id("preseed-test.env").show_with(&backend);
+        assert_eq!(answer.unwrap(), (Choice::Yes, Some("file".to_string())));
+    }
+
+    #[test]
+    fn unknown_tags_are_rejected() {
+        let json = r#"{"packages": ["vim", "nano"], "shell": "fish"}"#;
+        let backend = Preseed::from_json(json).unwrap();
+        let result = Checklist::new("Packages", 2, items(&["vim", "git"]))
+            .id("packages")
+            .show_with(&backend);
+        assert!(matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("'nano'")));
+        let result = Menu::new("Shell", 2, menu(&["bash", "zsh"])).id("shell").show_with(&backend);
+        assert!(matches!(result, Err(Error::InvalidConfig(ref msg)) if msg.contains("'fish'")));
+    }
+
+    #[test]
+    fn missing_answers_are_reported() {
+        let backend = Preseed::new();
+        let result = Input::new("Value").id("preseed-test-missing").show_with(&backend);
+        assert!(matches!(result, Err(Error::NoAnswer(ref msg))
+            if msg.contains("DIALOG_ANSWER_PRESEED_TEST_MISSING")));
+        let result = Input::new("Value").show_with(&backend);
+        assert!(matches!(result, Err(Error::InvalidConfig(_))));
+        assert!(Message::new("Hello").show_with(&backend).is_ok());
+    }
+
+    #[test]
+    fn form_values_end_with_newline() {
+        let backend = Preseed::from_toml(TOML).unwrap();
+        let fields = vec![
+            ("Name".to_string(), 1, 1, String::new(), 1, 10, 20, 0),
+            ("Password".to_string(), 2, 1, String::new(), 2, 10, 20, 0),
+        ];
+        let answer = Form::new("Account", 2, fields).id("account").show_with(&backend).unwrap();
+        assert_eq!(answer, (Choice::Yes, Some("jane\ns3cret\n".to_string())));
+        let answer = Form::new("Host", 1, Vec::new()).id("host").show_with(&backend).unwrap();
+        assert_eq!(answer, (Choice::Yes, Some("example.org\n".to_string())));
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backends::{Backend, Capabilities};
+use crate::{
+    Checklist, Choice, Error,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Request, Response, Result
+};
+
+// A recorded dialog box together with the answer of the user.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    request: Request,
+    response: Response,
+}
+
+fn checklist_response(output: &(Choice, Option<Vec<String>>)) -> Response {
+    Response::new(output.0, output.1.as_ref().map(|tags| tags.join("\n")))
+}
+
+fn choices_response(output: &(Choice, Option<String>)) -> Response {
+    Response::new(output.0, output.1.clone())
+}
+
+fn file_selection_response(output: &(Choice, Option<PathBuf>)) -> Response {
+    let path = output.1.as_ref().map(|path| path.to_string_lossy().into_owned());
+    Response::new(output.0, path)
+}
+
+fn unit_response(_: &()) -> Response {
+    Response::from(Choice::Yes)
+}
+
+fn choice_response(choice: &Choice) -> Response {
+    Response::from(*choice)
+}
+
+/// The `record` backend.
+///
+/// This backend wraps another backend and shows all dialog boxes using the wrapped backend.  Each
+/// dialog box and the answer of the user are appended to a file, one JSON object per line.  The
+/// recording can be played back using the [`Replay`][] backend.
+///
+/// Note that the recording contains all user input in plain text, including passwords.
+///
+/// # Example
+///
+/// ```no_run
+/// use dialog::DialogBox;
+///
+/// let backend = dialog::backends::Record::create(dialog::backends::Dialog::new(), "session.jsonl")
+///     .expect("Could not create recording");
+/// dialog::Question::new("Do you want to continue?")
+///     .show_with(&backend)
+///     .expect("Could not display dialog box");
+/// ```
+///
+/// [`Replay`]: struct.Replay.html
+#[derive(Debug)]
+pub struct Record<B: Backend> {
+    backend: B,
+    file: RefCell<fs::File>,
+}
+
+impl<B: Backend> Record<B> {
+    /// Creates a new `Record` instance that wraps the given backend and writes the recording to
+    /// the given file.
+    ///
+    /// If the file already exists, it is truncated.  A new file is only readable by the current
+    /// user, as the recording may contain passwords.
+    pub fn create(backend: B, path: impl AsRef<Path>) -> Result<Record<B>> {
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        Ok(Record {
+            backend,
+            file: RefCell::new(file),
+        })
+    }
+
+    /// Returns the wrapped backend.
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+
+    fn record<T>(&self, request: Request, output: Result<T>, response: fn(&T) -> Response)
+        -> Result<T> {
+        let output = output?;
+        let entry = Entry {
+            request,
+            response: response(&output),
+        };
+        let mut file = self.file.borrow_mut();
+        serde_json::to_writer(&mut *file, &entry)?;
+        writeln!(file)?;
+        file.flush()?;
+        Ok(output)
+    }
+}
+
+impl<B: Backend> AsRef<Record<B>> for Record<B> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<B: Backend> Backend for Record<B> {
+    fn capabilities(&self) -> Capabilities {
+        self.backend.capabilities()
+    }
+
+    fn can_run(&self) -> bool {
+        self.backend.can_run()
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        self.record(checklist.into(), self.backend.show_checklist(checklist), checklist_response)
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        let output = self.backend.show_file_selection(file_selection);
+        self.record(file_selection.into(), output, file_selection_response)
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.record(form.into(), self.backend.show_form(form), choices_response)
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.record(gauge.into(), self.backend.show_gauge(gauge), unit_response)
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.record(input.into(), self.backend.show_input(input), choices_response)
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        self.record(menu.into(), self.backend.show_menu(menu), choices_response)
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.record(message.into(), self.backend.show_message(message), unit_response)
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.record(form.into(), self.backend.show_mixed_form(form), choices_response)
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.record(gauge.into(), self.backend.show_mixed_gauge(gauge), unit_response)
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.record(password.into(), self.backend.show_password(password), choices_response)
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.record(form.into(), self.backend.show_password_form(form), choices_response)
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.record(question.into(), self.backend.show_question(question), choice_response)
+    }
+}
+
+/// The `replay` backend.
+///
+/// This backend plays back a recording created by the [`Record`][] backend without displaying
+/// anything.  Each dialog box is answered with the recorded response.  If the type and the id, or
+/// the text for dialog boxes without an id, do not match the next dialog box of the recording, or
+/// if the recording has ended, an error is returned.  Other settings, like the start directory of
+/// a file selection, may differ between machines and are not compared.
+///
+/// # Example
+///
+/// ```
+/// use dialog::{backends, Choice, DialogBox};
+///
+/// let name = format!("dialog-replay-example-{}.jsonl", std::process::id());
+/// let path = std::env::temp_dir().join(name);
+/// let mock = backends::Mock::new();
+/// mock.push_response(Choice::No);
+/// let backend = backends::Record::create(mock, &path).expect("Could not create recording");
+/// dialog::Question::new("Do you want to continue?")
+///     .show_with(&backend)
+///     .expect("Could not display dialog box");
+///
+/// let backend = backends::Replay::open(&path).expect("Could not read recording");
+/// let choice = dialog::Question::new("Do you want to continue?")
+///     .show_with(&backend)
+///     .expect("Could not replay dialog box");
+/// assert_eq!(choice, Choice::No);
+/// assert_eq!(backend.remaining(), 0);
+/// # std::fs::remove_file(&path).expect("Could not remove recording");
+/// ```
+///
+/// [`Record`]: struct.Record.html
+#[derive(Debug)]
+pub struct Replay {
+    entries: RefCell<VecDeque<Entry>>,
+}
+
+impl Replay {
+    /// Creates a new `Replay` instance that plays back the recording in the given file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Replay> {
+        Replay::from_reader(io::BufReader::new(fs::File::open(path)?))
+    }
+
+    /// Creates a new `Replay` instance that plays back the recording read from the given reader.
+    pub fn from_reader(reader: impl BufRead) -> Result<Replay> {
+        let mut entries = VecDeque::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push_back(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(Replay {
+            entries: RefCell::new(entries),
+        })
+    }
+
+    /// Returns the number of recorded dialog boxes that have not been played back yet.
+    pub fn remaining(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    fn replay(&self, request: Request) -> Result<Response> {
+        let mut entries = self.entries.borrow_mut();
+        match entries.front() {
+            Some(entry) if matches(&entry.request, &request) => {
+                Ok(entries.pop_front().map(|entry| entry.response).unwrap())
+            }
+            Some(entry) => Err(Error::Error(format!(
+                "dialog box does not match the recording: expected {:?} dialog {:?}, got {:?} \
+                 dialog {:?}",
+                entry.request.kind(), entry.request.text(), request.kind(), request.text()
+            ))),
+            None => Err(Error::Error(format!(
+                "recording has ended, no response for {:?} dialog", request.kind()
+            ))),
+        }
+    }
+
+    fn replay_choices(&self, request: Request) -> Result<(Choice, Option<String>)> {
+        self.replay(request).map(|response| (response.choice, response.value))
+    }
+}
+
+// Checks whether a dialog box matches a recorded dialog box.  Dialog boxes with an id are matched
+// by id, all others by text.
+fn matches(recorded: &Request, request: &Request) -> bool {
+    let same = match (recorded.id(), request.id()) {
+        (Some(recorded), Some(id)) => recorded == id,
+        _ => recorded.text() == request.text(),
+    };
+    recorded.kind() == request.kind() && same
+}
+
+impl AsRef<Replay> for Replay {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl Backend for Replay {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::all_boxes()
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let (choice, value) = self.replay_choices(checklist.into())?;
+        let tags = value.map(|value| value.lines().map(ToString::to_string).collect());
+        Ok((choice, tags))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.replay_choices(file_selection.into())
+            .map(|(choice, path)| (choice, path.map(PathBuf::from)))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.replay_choices(form.into())
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.replay(gauge.into()).map(|_| ())
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.replay_choices(input.into())
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        self.replay_choices(menu.into())
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.replay(message.into()).map(|_| ())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.replay_choices(form.into())
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.replay(gauge.into()).map(|_| ())
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.replay_choices(password.into())
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.replay_choices(form.into())
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.replay(question.into()).map(|response| response.choice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+    use crate::backends::Mock;
+    use crate::DialogBox;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("dialog-record-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    #[test]
+    fn recording_is_private() {
+        let path = temp_path("private");
+        let _ = fs::remove_file(&path);
+        let record = Record::create(Mock::new(), &path);
+        let mode = fs::metadata(&path).map(|metadata| metadata.permissions().mode());
+        fs::remove_file(&path).unwrap();
+
+        record.unwrap();
+        assert_eq!(mode.unwrap() & 0o777, 0o600);
+    }
+
+    fn record(name: &str, show: impl FnOnce(&Record<Mock>)) -> Replay {
+        let path = temp_path(name);
+        let mock = Mock::new();
+        mock.push_response((Choice::Yes, Some("/home/alice/a")));
+        let record = Record::create(mock, &path).unwrap();
+        show(&record);
+        drop(record);
+        let replay = Replay::open(&path);
+        fs::remove_file(&path).unwrap();
+        replay.unwrap()
+    }
+
+    #[test]
+    fn replay_ignores_settings() {
+        let backend = record("settings", |record| {
+            FileSelection::new("Open").path("/home/alice").show_with(record).unwrap();
+        });
+        let output = FileSelection::new("Open").path("/home/bob").show_with(&backend).unwrap();
+        assert_eq!(output, (Choice::Yes, Some(PathBuf::from("/home/alice/a"))));
+    }
+
+    #[test]
+    fn replay_matches_ids() {
+        let backend = record("ids", |record| {
+            Message::new("Hello").id("greeting").show_with(record).unwrap();
+            Message::new("Bye").show_with(record).unwrap();
+        });
+        Message::new("Hello, world").id("greeting").show_with(&backend).unwrap();
+        assert!(Message::new("Bye").id("farewell").show_with(&backend).is_ok());
+        assert_eq!(backend.remaining(), 0);
+    }
+
+    #[test]
+    fn replay_rejects_other_dialog_boxes() {
+        let backend = record("mismatch", |record| {
+            Message::new("Hello").show_with(record).unwrap();
+        });
+        assert!(Message::new("Goodbye").show_with(&backend).is_err());
+        assert!(Question::new("Hello").show_with(&backend).is_err());
+        assert_eq!(backend.remaining(), 1);
+        Message::new("Hello").show_with(&backend).unwrap();
+        assert_eq!(backend.remaining(), 0);
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+use crate::backends::Capabilities;
+use crate::{
+    BoxKind, Checklist, Choice, Error,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Result
+};
+
+/// The `rofi` backend.
+///
+/// This backend uses the external `rofi` program in its dmenu mode, or alternatively the plain
+/// `dmenu` program, to display menus, inputs, passwords and questions as launcher popups.  This
+/// is useful for tools that are started from a window manager instead of a terminal.  All other
+/// dialog boxes are not supported by this backend.
+///
+/// `dmenu` has no password mode, so password dialog boxes can only be shown with `rofi`.
+#[derive(Debug, Default)]
+pub struct Rofi {
+    dmenu: bool,
+}
+
+impl Rofi {
+    /// Creates a new `Rofi` instance that uses `rofi -dmenu`.
+    pub fn new() -> Rofi {
+        Default::default()
+    }
+
+    /// Creates a new `Rofi` instance that uses the plain `dmenu` program.
+    pub fn dmenu() -> Rofi {
+        Rofi { dmenu: true }
+    }
+
+    fn execute(&self, prompt: &str, args: Vec<&str>, lines: &[String]) -> Result<process::Output> {
+        let mut command = if self.dmenu {
+            process::Command::new("dmenu")
+        } else {
+            let mut command = process::Command::new("rofi");
+            command.arg("-dmenu");
+            command
+        };
+        command.arg("-p").arg(prompt);
+        command.args(args);
+
+        let mut child = command
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::inherit())
+            .spawn()
+            .map_err(|err| Error::from((self.name(), err)))?;
+
+        {
+            let mut stdin = child.stdin.take().ok_or("launcher stdin not available")?;
+            for line in lines {
+                writeln!(stdin, "{}", line)?;
+            }
+        }
+        child.wait_with_output().map_err(Error::IoError)
+    }
+
+    fn name(&self) -> &'static str {
+        if self.dmenu {
+            "dmenu"
+        } else {
+            "rofi"
+        }
+    }
+
+    fn unsupported<T>(&self, boxtype: &str) -> Result<T> {
+        Err(Error::Unsupported(format!("{} dialogs with {}", boxtype, self.name())))
+    }
+
+    // Gets the button choice and the selected line.  Both rofi and dmenu exit with 1 if the user
+    // aborted the selection.
+    fn get_choices(&self, output: process::Output) -> Result<(Choice, Option<String>)> {
+        match output.status.code() {
+            Some(0) => {
+                let stdout = String::from_utf8(output.stdout)?;
+                let line = stdout.trim_end_matches(['\n', '\r']).to_string();
+                Ok((Choice::Yes, Some(line)))
+            }
+            Some(1) => Ok((Choice::Escape, None)),
+            _ => Err(Error::from((self.name(), output.status))),
+        }
+    }
+}
+
+impl AsRef<Rofi> for Rofi {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl super::Backend for Rofi {
+    fn capabilities(&self) -> Capabilities {
+        if self.dmenu {
+            Capabilities::with_boxes(&[BoxKind::Input, BoxKind::Menu, BoxKind::Question])
+        } else {
+            Capabilities::with_boxes(&[
+                BoxKind::Input,
+                BoxKind::Menu,
+                BoxKind::Password,
+                BoxKind::Question,
+            ])
+        }
+    }
+
+    fn can_run(&self) -> bool {
+        let x11 = env::var_os("DISPLAY").is_some();
+        let wayland = env::var_os("WAYLAND_DISPLAY").is_some() && !self.dmenu;
+        super::is_available(self.name()) && (x11 || wayland)
+    }
+
+    fn show_checklist(&self, _checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        self.unsupported("checklist")
+    }
+
+    fn show_file_selection(&self, _file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.unsupported("file selection")
+    }
+
+    fn show_form(&self, _form: &Form) -> Result<(Choice, Option<String>)> {
+        self.unsupported("form")
+    }
+
+    fn show_gauge(&self, _gauge: &Gauge) -> Result<()> {
+        self.unsupported("gauge")
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        let mut args = vec!["-l", "0"];
+        if let Some(ref default) = input.default {
+            if !self.dmenu {
+                args.push("-filter");
+                args.push(default);
+            }
+        }
+        self.execute(&input.text, args, &[])
+            .and_then(|output| self.get_choices(output))
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        let lines: Vec<String> = menu.list.chunks(2)
+            .map(|item| item.join("\t"))
+            .collect();
+        // rofi can print the index of the selected line, dmenu always prints the line itself
+        let args = if self.dmenu { vec!["-l", "10"] } else { vec!["-no-custom", "-format", "i"] };
+        let (choice, line) = self.execute(&menu.text, args, &lines)
+            .and_then(|output| self.get_choices(output))?;
+        Ok(menu_choice(choice, line, &lines, self.dmenu, &menu.list))
+    }
+
+    fn show_message(&self, _message: &Message) -> Result<()> {
+        self.unsupported("message")
+    }
+
+    fn show_mixed_form(&self, _form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.unsupported("mixed form")
+    }
+
+    fn show_mixed_gauge(&self, _gauge: &MixedGauge) -> Result<()> {
+        self.unsupported("mixed gauge")
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        if self.dmenu {
+            return self.unsupported("password");
+        }
+        self.execute(&password.text, vec!["-l", "0", "-password"], &[])
+            .and_then(|output| self.get_choices(output))
+    }
+
+    fn show_password_form(&self, _form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.unsupported("password form")
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        let lines = vec!["Yes".to_string(), "No".to_string()];
+        let args = if self.dmenu { vec![] } else { vec!["-no-custom"] };
+        let (choice, line) = self.execute(&question.text, args, &lines)
+            .and_then(|output| self.get_choices(output))?;
+        Ok(question_choice(choice, line.as_deref()))
+    }
+}
+
+// Maps the selected line of a menu to the tag of the item.  rofi prints the index of the line
+// and dmenu the line itself, or the typed text if it does not match an item.
+fn menu_choice(
+    choice: Choice,
+    line: Option<String>,
+    lines: &[String],
+    dmenu: bool,
+    list: &[String],
+) -> (Choice, Option<String>) {
+    let index = line.and_then(|line| {
+        if dmenu {
+            lines.iter().position(|l| *l == line)
+        } else {
+            line.parse::<usize>().ok().filter(|index| *index < lines.len())
+        }
+    });
+    match (choice, index) {
+        (Choice::Yes, Some(index)) => (Choice::Yes, Some(list[index * 2].clone())),
+        (Choice::Yes, None) => (Choice::Cancel, None),
+        (choice, _) => (choice, None),
+    }
+}
+
+// Maps the selected line of a question to a choice.  Text that the user typed instead of
+// selecting one of the lines is treated like an aborted selection.
+fn question_choice(choice: Choice, line: Option<&str>) -> Choice {
+    match (choice, line) {
+        (Choice::Yes, Some("Yes")) => Choice::Yes,
+        (Choice::Yes, Some("No")) => Choice::No,
+        (Choice::Yes, _) => Choice::Escape,
+        (choice, _) => choice,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> (Vec<String>, Vec<String>) {
+        let list: Vec<String> = ["a", "first item", "b", "second item"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let lines = vec!["a\tfirst item".to_string(), "b\tsecond item".to_string()];
+        (list, lines)
+    }
+
+    #[test]
+    fn question_accepts_only_exact_answers() {
+        assert_eq!(question_choice(Choice::Yes, Some("Yes")), Choice::Yes);
+        assert_eq!(question_choice(Choice::Yes, Some("No")), Choice::No);
+        assert_eq!(question_choice(Choice::Yes, Some("yes please")), Choice::Escape);
+        assert_eq!(question_choice(Choice::Yes, Some("")), Choice::Escape);
+        assert_eq!(question_choice(Choice::Escape, None), Choice::Escape);
+    }
+
+    #[test]
+    fn rofi_menu_uses_index() {
+        let (list, lines) = menu();
+        let choice = menu_choice(Choice::Yes, Some("1".to_string()), &lines, false, &list);
+        assert_eq!(choice, (Choice::Yes, Some("b".to_string())));
+        let choice = menu_choice(Choice::Yes, Some("2".to_string()), &lines, false, &list);
+        assert_eq!(choice, (Choice::Cancel, None));
+    }
+
+    #[test]
+    fn dmenu_menu_uses_line() {
+        let (list, lines) = menu();
+        let choice = menu_choice(Choice::Yes, Some("a\tfirst item".to_string()), &lines, true, &list);
+        assert_eq!(choice, (Choice::Yes, Some("a".to_string())));
+        let choice = menu_choice(Choice::Yes, Some("typed".to_string()), &lines, true, &list);
+        assert_eq!(choice, (Choice::Cancel, None));
+        let choice = menu_choice(Choice::Escape, None, &lines, true, &list);
+        assert_eq!(choice, (Choice::Escape, None));
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::request::RawResponse;
+use crate::{Error, Response, Result};
+
+// Converts the exit status, the answer and the diagnostics of the process into a response.
+type Parse = Box<dyn FnOnce(process::ExitStatus, Vec<u8>, Vec<u8>) -> Result<RawResponse> + Send>;
+
+#[derive(Debug, Default)]
+struct State {
+    // The process has exited and was reaped, so its pid must not be used anymore.
+    finished: bool,
+    closed: bool,
+}
+
+/// A dialog box that is displayed by a backend in the background.
+///
+/// A `Running` instance is returned by [`Backend::spawn`][].  Usually, you do not use it directly
+/// but through the [`Handle`][] returned by [`DialogBox::spawn_with`][].
+///
+/// If a `Running` instance is dropped before the dialog box has been closed, the dialog box is
+/// closed.
+///
+/// [`Backend::spawn`]: trait.Backend.html#method.spawn
+/// [`DialogBox::spawn_with`]: ../trait.DialogBox.html#method.spawn_with
+/// [`Handle`]: ../struct.Handle.html
+pub struct Running {
+    child: Option<process::Child>,
+    answer: Option<thread::JoinHandle<io::Result<Vec<u8>>>>,
+    stderr: Option<thread::JoinHandle<io::Result<Vec<u8>>>>,
+    parse: Option<Parse>,
+    closer: Closer,
+}
+
+impl Running {
+    // Creates a new instance for a spawned process that writes the answer to the given reader.
+    // The terminal settings have to be saved before the process is spawned so that they can be
+    // restored if the dialog box is closed.
+    pub(crate) fn new(mut child: process::Child, mut answer: impl Read + Send + 'static,
+        parse: Parse, terminal: Terminal) -> Running {
+        let mut stderr = child.stderr.take();
+        let closer = Closer {
+            pid: child.id() as libc::pid_t,
+            state: Arc::new(Mutex::new(State::default())),
+            terminal: Arc::new(terminal),
+        };
+        Running {
+            child: Some(child),
+            answer: Some(thread::spawn(move || {
+                let mut buf = Vec::new();
+                answer.read_to_end(&mut buf).map(|_| buf)
+            })),
+            stderr: Some(thread::spawn(move || {
+                let mut buf = Vec::new();
+                if let Some(ref mut stderr) = stderr {
+                    stderr.read_to_end(&mut buf)?;
+                }
+                Ok(buf)
+            })),
+            parse: Some(parse),
+            closer,
+        }
+    }
+
+    /// Returns a closer that can be used to close the dialog box, for example from another
+    /// thread.
+    pub fn closer(&self) -> Closer {
+        self.closer.clone()
+    }
+
+    /// Closes the dialog box.
+    ///
+    /// See [`Closer::close`][] for more information.
+    ///
+    /// [`Closer::close`]: struct.Closer.html#method.close
+    pub fn close(&self) -> Result<()> {
+        self.closer.close()
+    }
+
+    /// Checks whether the dialog box has been closed.
+    pub fn is_finished(&self) -> Result<bool> {
+        if self.child.is_none() {
+            return Ok(true);
+        }
+        has_exited(self.closer.pid)
+    }
+
+    /// Waits until the dialog box has been closed and returns the answer.
+    ///
+    /// If the dialog box has been closed using [`close`][] or a [`Closer`][], an
+    /// [`Error::Cancelled`][] error is returned.
+    ///
+    /// [`close`]: #method.close
+    /// [`Closer`]: struct.Closer.html
+    /// [`Error::Cancelled`]: ../enum.Error.html#variant.Cancelled
+    pub fn wait(self) -> Result<Response> {
+        self.wait_raw()?.into_response()
+    }
+
+    // Like wait, but returns the value as written by the backend.
+    pub(crate) fn wait_raw(mut self) -> Result<RawResponse> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<RawResponse> {
+        let mut child = self.child.take().ok_or("dialog box has already been waited for")?;
+
+        let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+        let flags = libc::WEXITED | libc::WNOWAIT;
+        loop {
+            let result = unsafe {
+                libc::waitid(libc::P_PID, child.id() as libc::id_t, &mut info, flags)
+            };
+            if result != -1 {
+                break;
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err.into());
+            }
+        }
+
+        let closed = {
+            let mut state = self.closer.state.lock().map_err(|_| "dialog box state poisoned")?;
+            state.finished = true;
+            state.closed
+        };
+        let status = child.wait()?;
+        if closed {
+            // Processes started by the backend could still hold the pipes open, so the reader
+            // threads are detached instead of joined.
+            self.closer.terminal.restore();
+            return Err(Error::Cancelled);
+        }
+        let answer = join(self.answer.take())?;
+        let stderr = join(self.stderr.take())?;
+        let parse = self.parse.take().ok_or("dialog box has already been waited for")?;
+        parse(status, answer, stderr)
+    }
+}
+
+// Checks whether the process has exited without reaping it so that the pid stays valid for the
+// closer.
+fn has_exited(pid: libc::pid_t) -> Result<bool> {
+    let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+    let flags = libc::WEXITED | libc::WNOWAIT | libc::WNOHANG;
+    let result = unsafe { libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, flags) };
+    if result == -1 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(unsafe { info.si_pid() } != 0)
+}
+
+fn join(thread: Option<thread::JoinHandle<io::Result<Vec<u8>>>>) -> Result<Vec<u8>> {
+    match thread {
+        Some(thread) => Ok(thread.join().map_err(|_| "could not read the dialog output")??),
+        None => Ok(Vec::new()),
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if self.child.is_some() {
+            let _ = self.close();
+            let _ = self.finish();
+        }
+    }
+}
+
+/// Closes a dialog box that is displayed in the background.
+///
+/// A `Closer` can be obtained from a [`Handle`][] or a [`Running`][] instance.  It can be cloned
+/// and sent to other threads.
+///
+/// [`Handle`]: ../struct.Handle.html
+/// [`Running`]: struct.Running.html
+#[derive(Clone)]
+pub struct Closer {
+    pid: libc::pid_t,
+    state: Arc<Mutex<State>>,
+    terminal: Arc<Terminal>,
+}
+
+impl Closer {
+    /// Closes the dialog box.
+    ///
+    /// The backend process is terminated and the terminal settings are restored.  Waiting for the
+    /// dialog box then returns an [`Error::Cancelled`][] error.  If the dialog box has already
+    /// been closed by the user, this method does nothing.
+    ///
+    /// [`Error::Cancelled`]: ../enum.Error.html#variant.Cancelled
+    pub fn close(&self) -> Result<()> {
+        let mut state = self.state.lock().map_err(|_| "dialog box state poisoned")?;
+        if state.finished || state.closed {
+            return Ok(());
+        }
+        // The process might have exited without being reaped yet.  Signalling it would still
+        // succeed, but the answer of the user must not be discarded.
+        if has_exited(self.pid)? {
+            return Ok(());
+        }
+        if unsafe { libc::kill(self.pid, libc::SIGTERM) } == -1 {
+            return Err(io::Error::last_os_error().into());
+        }
+        state.closed = true;
+        Ok(())
+    }
+}
+
+// The settings of the controlling terminal, saved before the dialog box was displayed.
+pub(crate) struct Terminal {
+    file: Option<fs::File>,
+    termios: Option<libc::termios>,
+}
+
+impl Terminal {
+    pub(crate) fn save() -> Terminal {
+        let file = fs::OpenOptions::new().read(true).write(true).open("/dev/tty").ok();
+        let termios = file.as_ref().and_then(|file| {
+            let mut termios: libc::termios = unsafe { mem::zeroed() };
+            match unsafe { libc::tcgetattr(file.as_raw_fd(), &mut termios) } {
+                0 => Some(termios),
+                _ => None,
+            }
+        });
+        Terminal { file, termios }
+    }
+
+    // Restores the saved settings and leaves the alternate screen used by curses applications.
+    pub(crate) fn restore(&self) {
+        if let (Some(file), Some(termios)) = (&self.file, &self.termios) {
+            unsafe {
+                libc::tcsetattr(file.as_raw_fd(), libc::TCSANOW, termios);
+            }
+            let mut file = file;
+            let _ = file.write_all(b"\x1b[?1049l\x1b[?25h");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::fmt;
+
+use crate::backends::Dialog;
+use crate::{Error, Request, Result};
+
+/// A POSIX shell script that displays a sequence of dialog boxes using `dialog`.
+///
+/// The script is created with [`Dialog::script`][] and uses the settings of the [`Dialog`][]
+/// backend.  Each dialog box is added with a variable name.  When the script is run, the answer
+/// of the user is stored in this variable and the exit status of `dialog` in the variable with the
+/// suffix `_status`, for example `name` and `name_status`.  For checklists, the variable contains
+/// one selected tag per line.  Messages, gauges and questions only set the status variable.
+///
+/// The script can be written with the [`Display`][] implementation.
+///
+/// # Example
+///
+/// ```
+/// let backend = dialog::backends::Dialog::new();
+/// let mut script = backend.script();
+/// script.push("name", &dialog::Input::new("Please enter your name"))
+///     .expect("Could not add dialog box");
+/// script.push("confirm", &dialog::Question::new("Is this correct?"))
+///     .expect("Could not add dialog box");
+/// let script = script.to_string();
+/// assert!(script.contains(
+///     "name=$(dialog --output-fd 3 --inputbox 'Please enter your name' 0 0 3>&1 1>&2)"
+/// ));
+/// assert!(script.contains("confirm_status=$?"));
+/// ```
+///
+/// [`Dialog`]: struct.Dialog.html
+/// [`Dialog::script`]: struct.Dialog.html#method.script
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Debug)]
+pub struct Script<'a> {
+    dialog: &'a Dialog,
+    lines: Vec<String>,
+}
+
+impl<'a> Script<'a> {
+    pub(crate) fn new(dialog: &'a Dialog) -> Script<'a> {
+        Script {
+            dialog,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Adds a dialog box to the end of the script and stores the answer in the variable with the
+    /// given name.
+    ///
+    /// The name must be a valid shell variable name.
+    pub fn push(&mut self, name: &str, request: impl Into<Request>) -> Result<()> {
+        let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(Error::InvalidConfig(format!("invalid shell variable name '{}'", name)));
+        }
+
+        let request = request.into();
+        let command = self.dialog.command_line(request.clone())?;
+        if command.args().iter().any(|arg| arg.to_str().is_none()) {
+            return Err(Error::InvalidConfig("script arguments must be valid UTF-8".to_string()));
+        }
+        let command = command.to_string();
+        // dialog fails if the output file descriptor is not open, even if the dialog box has no
+        // answer
+        let line = match request {
+            Request::Gauge { percent, .. } => format!("echo {} | {} 3>/dev/null", percent, command),
+            Request::Message { .. } | Request::MixedGauge { .. } | Request::Question { .. } => {
+                format!("{} 3>/dev/null", command)
+            }
+            // dialog writes the answer to the file descriptor 3 and draws the dialog box on
+            // stdout, so stdout is redirected to the terminal on stderr
+            _ => format!("{}=$({} 3>&1 1>&2)", name, command),
+        };
+        self.lines.push(line);
+        self.lines.push(format!("{}_status=$?", name));
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Script<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "#!/bin/sh")?;
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
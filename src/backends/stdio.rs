@@ -0,0 +1,235 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::ffi::OsString;
+use std::io::{self, BufRead, Write};
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+
+use crate::backends::Capabilities;
+use crate::{
+    Checklist, Choice,
+    FileSelection, FileSelectionMode, Form,
+    FormField, Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Result
+};
+
+/// The `stdio` backend.
+///
+/// This backend is intended as a fallback backend to use if no other backend is available.  The
+/// dialogs are printed to the standard output and user input is read from the standard input.
+/// Passwords are read from the terminal without echoing them.
+#[derive(Debug, Default)]
+pub struct Stdio {}
+
+impl Stdio {
+    /// Creates a new `Stdio` instance.
+    pub fn new() -> Stdio {
+        Default::default()
+    }
+}
+
+impl AsRef<Stdio> for Stdio {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+fn print(text: &str) -> Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "{}", text)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+// Reads a line without the line terminator, or None if the input is closed.
+fn read_line() -> Result<Option<String>> {
+    Ok(read_raw_line(&mut io::stdin().lock())?.map(String::from_utf8).transpose()?)
+}
+
+// Like read_line, but reads from the given input and keeps the line as bytes, as file names might
+// not be valid UTF-8.
+fn read_raw_line(input: &mut impl BufRead) -> Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    if input.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    while line.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+        line.pop();
+    }
+    Ok(Some(line))
+}
+
+fn prompt(text: &str, default: Option<&str>) -> Result<Option<String>> {
+    match default {
+        Some(default) if !default.is_empty() => print(&format!("{} [{}]: ", text, default))?,
+        _ => print(&format!("{}: ", text))?,
+    }
+    Ok(read_line()?.map(|line| match default {
+        Some(default) if line.is_empty() => default.to_string(),
+        _ => line,
+    }))
+}
+
+fn read_password(text: &str) -> Result<String> {
+    rpassword::prompt_password(format!("{}: ", text)).map_err(From::from)
+}
+
+fn show_form_fields(text: &str, list: &[FormField], password: bool)
+    -> Result<(Choice, Option<String>)> {
+    println!("{}", text);
+    let mut values: Vec<String> = Vec::new();
+    for (label, _, _, item, _, _, _, _) in list {
+        let label = label.trim_end_matches(':');
+        let value = if password {
+            Some(read_password(label)?)
+        } else {
+            prompt(label, Some(item))?
+        };
+        match value {
+            Some(value) => values.push(value),
+            None => return Ok((Choice::Escape, None)),
+        }
+    }
+    Ok((Choice::Yes, Some(values.join("\n"))))
+}
+
+impl super::Backend for Stdio {
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::all_boxes();
+        capabilities.save_mode = true;
+        capabilities
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        println!("{}", checklist.text);
+        for (tag, item, status) in &checklist.list {
+            println!("  [{}] {}  {}", if *status { "x" } else { " " }, tag, item);
+        }
+        let defaults: Vec<String> = checklist.list.iter()
+            .filter(|(_, _, status)| *status)
+            .map(|(tag, _, _)| tag.clone())
+            .collect();
+        loop {
+            let line = match prompt("Tags (separated by spaces)", Some(&defaults.join(" ")))? {
+                Some(line) => line,
+                None => return Ok((Choice::Escape, None)),
+            };
+            let tags: Vec<String> = line.split_whitespace().map(ToString::to_string).collect();
+            match tags.iter().find(|tag| !checklist.list.iter().any(|(t, _, _)| t == *tag)) {
+                Some(tag) => println!("Unknown tag: {}", tag),
+                None => return Ok((Choice::Yes, Some(tags))),
+            }
+        }
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        let dir = file_selection.path.as_deref().unwrap_or_else(|| Path::new("."));
+        loop {
+            print(&format!("{}: ", file_selection.text))?;
+            let line = match read_raw_line(&mut io::stdin().lock())? {
+                Some(line) => line,
+                None => return Ok((Choice::Escape, None)),
+            };
+            if line.is_empty() {
+                return Ok((Choice::Cancel, None));
+            }
+            let path = dir.join(OsString::from_vec(line));
+            if file_selection.mode == FileSelectionMode::Open && !path.exists() {
+                println!("File not found: {}", path.display());
+                continue;
+            }
+            return Ok((Choice::Yes, Some(path)));
+        }
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        show_form_fields(&form.text, &form.list, false)
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        println!("{}: {}%", gauge.text, gauge.percent);
+        Ok(())
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        match prompt(&input.text, input.default.as_deref())? {
+            Some(value) => Ok((Choice::Yes, Some(value))),
+            None => Ok((Choice::Escape, None)),
+        }
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        println!("{}", menu.text);
+        let items: Vec<&[String]> = menu.list.chunks(2).collect();
+        for item in &items {
+            println!("  {}  {}", item[0], item.get(1).map(String::as_str).unwrap_or(""));
+        }
+        loop {
+            let tag = match prompt("Choice", None)? {
+                Some(tag) => tag,
+                None => return Ok((Choice::Escape, None)),
+            };
+            if tag.is_empty() {
+                return Ok((Choice::Cancel, None));
+            }
+            if items.iter().any(|item| item[0] == tag) {
+                return Ok((Choice::Yes, Some(tag)));
+            }
+            println!("Unknown choice: {}", tag);
+        }
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        println!("{}", message.text);
+        Ok(())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        show_form_fields(&form.text, &form.list, false)
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        println!("{}: {}%", gauge.text, gauge.percent);
+        Ok(())
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        read_password(&password.text).map(|password| (Choice::Yes, Some(password)))
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        show_form_fields(&form.text, &form.list, true)
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        loop {
+            let answer = match prompt(&format!("{} [y/n]", question.text), None)? {
+                Some(answer) => answer,
+                None => return Ok(Choice::Escape),
+            };
+            match answer.to_lowercase().as_ref() {
+                "y" | "yes" => return Ok(Choice::Yes),
+                "n" | "no" => return Ok(Choice::No),
+                _ => println!("Please answer yes or no."),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_lines_are_read_as_bytes() {
+        let mut input: &[u8] = b"caf\xe9.txt\r\n\nlast";
+        assert_eq!(read_raw_line(&mut input).unwrap(), Some(b"caf\xe9.txt".to_vec()));
+        assert_eq!(read_raw_line(&mut input).unwrap(), Some(Vec::new()));
+        assert_eq!(read_raw_line(&mut input).unwrap(), Some(b"last".to_vec()));
+        assert_eq!(read_raw_line(&mut input).unwrap(), None);
+    }
+}
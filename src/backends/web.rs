@@ -0,0 +1,486 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::backends::Capabilities;
+use crate::{
+    Checklist, Choice,
+    FileSelection, Form,
+    Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Request, Response, Result
+};
+
+// The maximum size of a request body that is accepted by the server.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Default)]
+struct State {
+    // The sequence number of the current dialog box, used to detect outdated submissions.
+    seq: u64,
+    // The access token of the session.
+    token: String,
+    current: Option<Request>,
+    answer: Option<Response>,
+}
+
+// A function that passes the URL of a dialog box to the user.
+type UrlHandler = Box<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    answered: Condvar,
+    stopped: AtomicBool,
+}
+
+/// The `web` backend.
+///
+/// This backend starts an HTTP server on localhost and displays each dialog box as an HTML page
+/// that can be opened in a web browser, for example through an SSH port forwarding.  The server
+/// only accepts requests that contain a random token that is generated when the backend is
+/// created.  The URL stays the same for all dialog boxes: while no dialog box is displayed, the
+/// page reloads itself until the next dialog box is shown.  Use [`set_url_handler`][] to pass the
+/// URL to the user, or [`url`][] to query it.
+///
+/// The pages submit the button choice in the `choice` field (`yes`, `no`, `cancel`, `extra`,
+/// `help`, `escape` or `timeout`) and the entered values in `value` fields, together with the
+/// sequence number of the dialog box in the `seq` field.  Messages and questions do not have a value, and
+/// multiple values, for example of forms and checklists, are joined with newlines.  Gauges do not
+/// wait for an answer.  The path of a file selection is entered as text, so paths that are not
+/// valid UTF-8 are displayed and returned with replacement characters.
+///
+/// If the backend is created using the `DIALOG` environment variable, the port is read from the
+/// `DIALOG_WEB_PORT` environment variable and the URLs are printed to the standard error.
+///
+/// # Example
+///
+/// ```no_run
+/// use dialog::DialogBox;
+///
+/// let backend = dialog::backends::Web::bind(8080)
+///     .expect("Could not start web server")
+///     .set_url_handler(|url| println!("Please open {}", url));
+/// dialog::Message::new("Did you know that I am using the dialog crate?")
+///     .show_with(&backend)
+///     .expect("Could not display dialog box");
+/// ```
+///
+/// [`set_url_handler`]: #method.set_url_handler
+/// [`url`]: #method.url
+pub struct Web {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    url_handler: Option<UrlHandler>,
+    server: Option<thread::JoinHandle<()>>,
+}
+
+impl Web {
+    /// Creates a new `Web` instance listening on a random port.
+    pub fn new() -> Result<Web> {
+        Web::bind(0)
+    }
+
+    /// Creates a new `Web` instance listening on the given port of the loopback interface.
+    pub fn bind(port: u16) -> Result<Web> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let addr = listener.local_addr()?;
+        let state = State {
+            token: generate_token()?,
+            ..Default::default()
+        };
+        let shared = Arc::new(Shared {
+            state: Mutex::new(state),
+            answered: Condvar::new(),
+            stopped: AtomicBool::new(false),
+        });
+
+        let server_shared = shared.clone();
+        let server = thread::spawn(move || serve(listener, server_shared));
+        Ok(Web {
+            addr,
+            shared,
+            url_handler: None,
+            server: Some(server),
+        })
+    }
+
+    /// Sets a function that is called with the URL of each dialog box when it is displayed.
+    ///
+    /// The URL contains the access token and is the same for all dialog boxes of this backend.
+    pub fn set_url_handler(mut self, handler: impl Fn(&str) + Send + Sync + 'static) -> Web {
+        self.url_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Returns the URL of the dialog pages, including the access token.
+    pub fn url(&self) -> Result<String> {
+        let state = self.shared.state.lock().map_err(|_| "web server state poisoned")?;
+        Ok(self.url_for(&state.token))
+    }
+
+    fn url_for(&self, token: &str) -> String {
+        format!("http://{}/?token={}", self.addr, token)
+    }
+
+    fn show(&self, request: Request, wait: bool) -> Result<Response> {
+        let mut state = self.shared.state.lock().map_err(|_| "web server state poisoned")?;
+        state.seq += 1;
+        state.current = Some(request);
+        state.answer = None;
+        if let Some(ref handler) = self.url_handler {
+            handler(&self.url_for(&state.token));
+        }
+        if !wait {
+            return Ok(Response::from(Choice::Yes));
+        }
+
+        loop {
+            if let Some(answer) = state.answer.take() {
+                state.current = None;
+                return Ok(answer);
+            }
+            state = self.shared.answered.wait(state).map_err(|_| "web server state poisoned")?;
+        }
+    }
+
+    fn show_choices(&self, request: Request) -> Result<(Choice, Option<String>)> {
+        self.show(request, true).map(|response| (response.choice, response.value))
+    }
+}
+
+impl fmt::Debug for Web {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Web")
+            .field("addr", &self.addr)
+            .field("url_handler", &self.url_handler.is_some())
+            .finish()
+    }
+}
+
+impl AsRef<Web> for Web {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl Drop for Web {
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::SeqCst);
+        // Wake up the server thread that is blocked in accept
+        let _ = TcpStream::connect(self.addr);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+    }
+}
+
+fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// Compares two strings in a time that does not depend on the position of the first difference, so
+// that the token cannot be guessed byte by byte.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn serve(listener: TcpListener, shared: Arc<Shared>) {
+    for stream in listener.incoming() {
+        if shared.stopped.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Ok(stream) = stream {
+            // Errors only affect the current connection, the browser can simply retry.
+            let _ = handle(stream, &shared);
+        }
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    query: String,
+    body: String,
+}
+
+fn read_request(stream: &TcpStream) -> io::Result<HttpRequest> {
+    let mut reader = io::BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let query = target.split_once('?').map(|(_, query)| query).unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "request body too large"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(HttpRequest {
+        method,
+        query,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn write_response(mut stream: &TcpStream, status: &str, headers: &str, body: &str)
+    -> io::Result<()> {
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\n\
+        Content-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n{}\r\n{}",
+        status, body.len(), headers, body)?;
+    stream.flush()
+}
+
+fn handle(stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let request = read_request(&stream)?;
+    let query = parse_form(&request.query);
+    let mut state = shared.state.lock()
+        .map_err(|_| io::Error::other("web server state poisoned"))?;
+    if !query.iter().any(|(name, value)| name == "token" && constant_time_eq(value, &state.token)) {
+        return write_response(&stream, "403 Forbidden", "", &page("Forbidden", "", false));
+    }
+
+    if request.method == "POST" {
+        let fields = parse_form(&request.body);
+        let seq = fields.iter().find(|(name, _)| name == "seq").map(|(_, value)| value.as_str());
+        if state.current.is_some() && state.answer.is_none() && seq == Some(&state.seq.to_string()) {
+            state.answer = Some(parse_response(&fields));
+            shared.answered.notify_all();
+        }
+        let location = format!("Location: /?token={}\r\n", state.token);
+        return write_response(&stream, "303 See Other", &location, "");
+    }
+
+    let body = match state.current {
+        Some(ref request) if state.answer.is_none() => render(request, state.seq),
+        _ => page("Waiting for the next dialog box", "<p>Waiting for the next dialog box...</p>",
+            true),
+    };
+    write_response(&stream, "200 OK", "", &body)
+}
+
+fn parse_form(data: &str) -> Vec<(String, String)> {
+    data.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (url_decode(name), url_decode(value))
+        })
+        .collect()
+}
+
+fn url_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'+', _) => decoded.push(b' '),
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 2;
+            }
+            (b, _) => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_response(fields: &[(String, String)]) -> Response {
+    let choice = match fields.iter().find(|(name, _)| name == "choice").map(|(_, v)| v.as_str()) {
+        Some("yes") => Choice::Yes,
+        Some("no") => Choice::No,
+        Some("cancel") => Choice::Cancel,
+        Some("extra") => Choice::Extra,
+        Some("help") => Choice::Help,
+        Some("timeout") => Choice::Timeout,
+        _ => Choice::Escape,
+    };
+    let values: Vec<&str> = fields.iter()
+        .filter(|(name, _)| name == "value")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    let value = if values.is_empty() { None } else { Some(values.join("\n")) };
+    Response::new(choice, value)
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn page(title: &str, content: &str, refresh: bool) -> String {
+    let refresh = if refresh { "<meta http-equiv=\"refresh\" content=\"1\">" } else { "" };
+    format!("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">{}<title>{}</title></head>\
+        <body>{}</body></html>\n", refresh, escape(title), content)
+}
+
+fn button(choice: &str, label: &str) -> String {
+    format!("<button type=\"submit\" name=\"choice\" value=\"{}\">{}</button> ", choice, label)
+}
+
+fn text_field(label: &str, value: &str, input_type: &str) -> String {
+    format!("<p><label>{} <input type=\"{}\" name=\"value\" value=\"{}\"></label></p>",
+        escape(label), input_type, escape(value))
+}
+
+fn render(request: &Request, seq: u64) -> String {
+    let mut fields = String::new();
+    let mut buttons = button("yes", "OK") + &button("cancel", "Cancel");
+    let mut refresh = false;
+    match *request {
+        Request::Checklist { ref list, .. } => {
+            for (tag, item, status) in list {
+                let _ = write!(fields, "<p><label><input type=\"checkbox\" name=\"value\" \
+                    value=\"{}\"{}> {} {}</label></p>", escape(tag),
+                    if *status { " checked" } else { "" }, escape(tag), escape(item));
+            }
+        }
+        Request::FileSelection { ref path, .. } => {
+            let path = path.as_ref().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+            fields = text_field("Path:", &path, "text");
+        }
+        Request::Form { ref list, .. } | Request::MixedForm { ref list, .. } => {
+            for (label, _, _, item, _, _, _, _) in list {
+                fields += &text_field(label, item, "text");
+            }
+        }
+        Request::PasswordForm { ref list, .. } => {
+            for (label, _, _, _, _, _, _, _) in list {
+                fields += &text_field(label, "", "password");
+            }
+        }
+        Request::Gauge { percent, .. } | Request::MixedGauge { percent, .. } => {
+            fields = format!("<p><progress value=\"{}\" max=\"100\">{}%</progress></p>",
+                percent, percent);
+            buttons.clear();
+            refresh = true;
+        }
+        Request::Input { ref default, .. } => {
+            fields = text_field("", default.as_deref().unwrap_or_default(), "text");
+        }
+        Request::Menu { ref list, .. } => {
+            for (i, [tag, item]) in list.iter().enumerate() {
+                let _ = write!(fields, "<p><label><input type=\"radio\" name=\"value\" \
+                    value=\"{}\"{}> {} {}</label></p>", escape(tag),
+                    if i == 0 { " checked" } else { "" }, escape(tag), escape(item));
+            }
+        }
+        Request::Message { .. } => buttons = button("yes", "OK"),
+        Request::Password { .. } => fields = text_field("", "", "password"),
+        Request::Question { .. } => buttons = button("yes", "Yes") + &button("no", "No"),
+    }
+
+    let text = escape(request.text()).replace('\n', "<br>");
+    let content = if buttons.is_empty() {
+        format!("<p>{}</p>{}", text, fields)
+    } else {
+        format!("<form method=\"post\"><p>{}</p>{}<input type=\"hidden\" name=\"seq\" \
+            value=\"{}\"><p>{}</p></form>", text, fields, seq, buttons)
+    };
+    page(request.text().lines().next().unwrap_or_default(), &content, refresh)
+}
+
+impl super::Backend for Web {
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::all_boxes();
+        capabilities.save_mode = true;
+        capabilities
+    }
+
+    fn show_checklist(&self, checklist: &Checklist) -> Result<(Choice, Option<Vec<String>>)> {
+        let (choice, value) = self.show_choices(checklist.into())?;
+        let tags = match choice {
+            Choice::Yes => Some(value.unwrap_or_default().lines().map(ToString::to_string).collect()),
+            _ => None,
+        };
+        Ok((choice, tags))
+    }
+
+    fn show_file_selection(&self, file_selection: &FileSelection) -> Result<(Choice, Option<PathBuf>)> {
+        self.show_choices(file_selection.into())
+            .map(|(choice, path)| (choice, path.map(PathBuf::from)))
+    }
+
+    fn show_form(&self, form: &Form) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_gauge(&self, gauge: &Gauge) -> Result<()> {
+        self.show(gauge.into(), false).map(|_| ())
+    }
+
+    fn show_input(&self, input: &Input) -> Result<(Choice, Option<String>)> {
+        self.show_choices(input.into())
+    }
+
+    fn show_menu(&self, menu: &Menu) -> Result<(Choice, Option<String>)> {
+        self.show_choices(menu.into())
+    }
+
+    fn show_message(&self, message: &Message) -> Result<()> {
+        self.show(message.into(), true).map(|_| ())
+    }
+
+    fn show_mixed_form(&self, form: &MixedForm) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_mixed_gauge(&self, gauge: &MixedGauge) -> Result<()> {
+        self.show(gauge.into(), false).map(|_| ())
+    }
+
+    fn show_password(&self, password: &Password) -> Result<(Choice, Option<String>)> {
+        self.show_choices(password.into())
+    }
+
+    fn show_password_form(&self, form: &PasswordForm) -> Result<(Choice, Option<String>)> {
+        self.show_choices(form.into())
+    }
+
+    fn show_question(&self, question: &Question) -> Result<Choice> {
+        self.show(question.into(), true).map(|response| response.choice)
+    }
+}
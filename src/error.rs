@@ -1,6 +1,7 @@
 // Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
 // SPDX-License-Identifier: MIT
 
+use std::error;
 use std::fmt;
 use std::io;
 use std::process;
@@ -13,6 +14,7 @@ pub type Result<T> = result::Result<T, Error>;
 
 /// An error returned by `dialog`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// A general error with an error message.
     Error(String),
@@ -20,6 +22,35 @@ pub enum Error {
     IoError(io::Error),
     /// An UTF-8 error.
     Utf8Error(str::Utf8Error),
+    /// A feature or dialog box that is not supported by the backend.
+    Unsupported(String),
+    /// The executable of a backend, for example `dialog`, could not be found, or none of the
+    /// backends can be used.
+    NotFound(String),
+    /// There is no terminal to display the dialog box on.
+    NoTerminal(String),
+    /// The terminal is too small to display the dialog box.
+    TerminalTooSmall(String),
+    /// A command exited with an unexpected exit status.
+    CommandFailed {
+        /// The name of the command.
+        command: String,
+        /// The exit status of the command.
+        status: process::ExitStatus,
+        /// The diagnostics printed by the command on the standard error, if captured.
+        stderr: String,
+    },
+    /// The configuration of a dialog box or a backend is invalid.
+    InvalidConfig(String),
+    /// The output of a backend or an input file could not be parsed.
+    ///
+    /// The error contains a description of the parsed data and the error of the parser.
+    ParseError(String, Box<dyn error::Error + Send + Sync>),
+    /// The dialog box was closed by the application before the user answered it.
+    Cancelled,
+    /// A backend that answers dialog boxes without asking the user, for example the preseed
+    /// backend, has no answer for the dialog box.
+    NoAnswer(String),
 }
 
 impl fmt::Display for Error {
@@ -28,6 +59,35 @@ impl fmt::Display for Error {
             Error::Error(ref s) => write!(f, "Error: {}", s),
             Error::IoError(ref e) => write!(f, "I/O error: {}", e),
             Error::Utf8Error(ref e) => write!(f, "UTF-8 error: {}", e),
+            Error::Unsupported(ref s) => write!(f, "Unsupported: {}", s),
+            Error::NotFound(ref s) => write!(f, "Command not found: {}", s),
+            Error::NoTerminal(ref s) => write!(f, "No terminal: {}", s),
+            Error::TerminalTooSmall(ref s) => write!(f, "Terminal too small: {}", s),
+            Error::CommandFailed { ref command, status, ref stderr } => {
+                match status.code() {
+                    Some(code) => write!(f, "Command {} failed with exit status {}", command, code)?,
+                    None => write!(f, "Command {} was terminated by a signal", command)?,
+                }
+                if !stderr.is_empty() {
+                    write!(f, ": {}", stderr)?;
+                }
+                Ok(())
+            }
+            Error::InvalidConfig(ref s) => write!(f, "Invalid configuration: {}", s),
+            Error::ParseError(ref s, ref e) => write!(f, "Parse error: {}: {}", s, e),
+            Error::Cancelled => write!(f, "The dialog box was closed"),
+            Error::NoAnswer(ref s) => write!(f, "No answer: {}", s),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::IoError(ref e) => Some(e),
+            Error::Utf8Error(ref e) => Some(e),
+            Error::ParseError(_, ref e) => Some(&**e),
+            _ => None,
         }
     }
 }
@@ -56,13 +116,34 @@ impl From<string::FromUtf8Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Error {
+        if error.is_io() {
+            Error::IoError(error.into())
+        } else {
+            Error::ParseError("could not parse JSON".to_string(), Box::new(error))
+        }
+    }
+}
+
 impl From<(&str, process::ExitStatus)> for Error {
     fn from(data: (&str, process::ExitStatus)) -> Error {
         let (command, status) = data;
-        let msg = match status.code() {
-            Some(code) => format!("Command {} failed with exit status {}", command, code),
-            None => format!("Command {} was terminated by a signal", command),
-        };
-        Error::Error(msg)
+        Error::CommandFailed {
+            command: command.to_string(),
+            status,
+            stderr: String::new(),
+        }
+    }
+}
+
+impl From<(&str, io::Error)> for Error {
+    fn from(data: (&str, io::Error)) -> Error {
+        let (command, error) = data;
+        if error.kind() == io::ErrorKind::NotFound {
+            Error::NotFound(command.to_string())
+        } else {
+            Error::IoError(error)
+        }
     }
 }
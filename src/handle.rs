@@ -0,0 +1,104 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use crate::backends::{Closer, Running};
+use crate::request::RawResponse;
+use crate::{Response, Result};
+
+/// A handle to a dialog box that is displayed in the background.
+///
+/// A handle is returned by [`DialogBox::spawn_with`][].  Use [`wait`][] to wait for the answer
+/// of the user, or [`close`][] to close the dialog box early.  Waiting for a dialog box that has
+/// been closed returns an [`Error::Cancelled`][] error.  If the handle is dropped while the dialog
+/// box is still displayed, the dialog box is closed.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// use dialog::DialogBox;
+///
+/// let handle = dialog::Question::new("Do you want to continue?")
+///     .spawn_with(dialog::backends::Dialog::new())
+///     .expect("Could not display dialog box");
+/// let closer = handle.closer();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_secs(10));
+///     closer.close().expect("Could not close dialog box");
+/// });
+/// match handle.wait() {
+///     Ok(choice) => println!("The user chose {:?}", choice),
+///     Err(dialog::Error::Cancelled) => println!("The dialog box timed out"),
+///     Err(err) => eprintln!("Could not display dialog box: {}", err),
+/// }
+/// ```
+///
+/// [`DialogBox::spawn_with`]: trait.DialogBox.html#method.spawn_with
+/// [`wait`]: #method.wait
+/// [`close`]: #method.close
+/// [`Error::Cancelled`]: enum.Error.html#variant.Cancelled
+pub struct Handle<T> {
+    running: Running,
+    output: Output<T>,
+}
+
+// Converts the answer of the backend into the output of the dialog box.
+enum Output<T> {
+    Response(fn(Response) -> T),
+    // Used for file selections so that file names that are not valid UTF-8 are preserved.
+    Raw(fn(RawResponse) -> T),
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(running: Running, output: fn(Response) -> T) -> Handle<T> {
+        Handle {
+            running,
+            output: Output::Response(output),
+        }
+    }
+
+    pub(crate) fn with_raw_output(running: Running, output: fn(RawResponse) -> T) -> Handle<T> {
+        Handle {
+            running,
+            output: Output::Raw(output),
+        }
+    }
+
+    /// Returns a closer that can be used to close the dialog box, for example from another
+    /// thread.
+    pub fn closer(&self) -> Closer {
+        self.running.closer()
+    }
+
+    /// Closes the dialog box and restores the terminal settings.
+    ///
+    /// If the dialog box has already been closed, this method does nothing.
+    pub fn close(&self) -> Result<()> {
+        self.running.close()
+    }
+
+    /// Checks whether the dialog box has been closed, either by the user or using [`close`][].
+    ///
+    /// [`close`]: #method.close
+    pub fn is_finished(&self) -> Result<bool> {
+        self.running.is_finished()
+    }
+
+    /// Waits until the dialog box has been closed and returns the output.
+    ///
+    /// If the dialog box has been closed using [`close`][] or a [`Closer`][], an
+    /// [`Error::Cancelled`][] error is returned.
+    ///
+    /// [`close`]: #method.close
+    /// [`Closer`]: backends/struct.Closer.html
+    /// [`Error::Cancelled`]: enum.Error.html#variant.Cancelled
+    pub fn wait(self) -> Result<T> {
+        let response = self.running.wait_raw()?;
+        match self.output {
+            Output::Response(output) => response.into_response().map(output),
+            Output::Raw(output) => Ok(output(response)),
+        }
+    }
+}
@@ -7,6 +7,7 @@
 //!
 //! The `dialog` crate can be used to display different types of dialog boxes.  The supported types
 //! are:
+//! - [`Checklist`][]: a checklist box
 //! - [`FileSelection`][]: a file chooser dialog box
 //! - [`Form`][]: a form
 //! - [`Gauge`][]: a gauge
@@ -19,13 +20,41 @@
 //! - [`PasswordForm`][]: a password form
 //! - [`Question`][]: a question dialog box
 //!
-//! These dialog boxes can be displayed using only one type of backend:
-//! - [`Dialog`][]: uses `dialog` to display ncurses-based dialog boxes (requires the external
-//!   `dialog` tool)
+//! These dialog boxes can be displayed using various backends:
+//! - [`Broker`][]: forwards dialog boxes to a [`BrokerServer`][] that displays them on its own
+//!   terminal
+//! - [`Dialog`][]: uses `dialog` or `whiptail` to display ncurses-based dialog boxes (requires
+//!   the external `dialog` or `whiptail` tool)
+//! - [`Fallback`][]: tries a list of backends and uses the first one that can display the dialog
+//!   box
+//! - [`Frontend`][]: sends dialog boxes to an external frontend process using a JSON lines
+//!   protocol
+//! - [`Fzf`][]: uses `fzf` for menus, checklists and file selections with fuzzy filtering and
+//!   plain prompts for all other dialog boxes (requires the external `fzf` tool)
+//! - [`Gum`][]: uses the subcommands of `gum` to display dialog boxes in the terminal (requires
+//!   the external `gum` tool)
+//! - [`Mock`][]: answers dialog boxes with queued responses and records them for inspection in
+//!   tests
+//! - [`Preseed`][]: answers dialog boxes from a preseed file or from environment variables
+//! - [`Record`][]: wraps another backend and records all dialog boxes and answers to a file
+//! - [`Replay`][]: answers dialog boxes from a recording created by [`Record`][]
+//! - [`Rofi`][]: uses `rofi -dmenu` or `dmenu` to display menus, inputs, passwords and questions
+//!   as launcher popups (requires the external `rofi` or `dmenu` tool)
+//! - [`Web`][]: displays dialog boxes as HTML pages served on localhost
+//! - [`Stdio`][]: prints dialog boxes to the standard output and reads the user input from the
+//!   standard input
 //!
 //! You can let `dialog` choose the backend by calling the [`show`][] method on a dialog box.  If
 //! you want to choose the backend yourself, create a backend instance and pass it to
 //! [`show_with`][].  You can also use the [`default_backend`][] function to create a backend.
+//! Not all backends support all dialog box types and features; use
+//! [`Backend::capabilities`][] to check what a backend supports.
+//!
+//! To display a dialog box in the background, use [`spawn_with`][].  The returned [`Handle`][]
+//! can be used to wait for the answer or to close the dialog box early.
+//!
+//! If the `tokio` feature is enabled, dialog boxes can also be shown without blocking an async
+//! executor using the `AsyncDialogBox` trait and a backend implementing `AsyncBackend`.
 //!
 //! # Examples
 //!
@@ -44,11 +73,11 @@
 //! ```no_run
 //! use dialog::DialogBox;
 //!
-//! let mut backend = dialog::backends::Dialog::new();
-//! backend.set_backtitle("dialog demo");
-//! backend.set_title("box title");
-//! backend.set_width(100);
-//! backend.set_height(10);
+//! let backend = dialog::backends::Dialog::new()
+//!     .set_backtitle("dialog demo")
+//!     .set_title("box title")
+//!     .set_width(100)
+//!     .set_height(10);
 //! dialog::Message::new("Did you know that I am using the dialog crate?")
 //!     .show_with(&backend)
 //!     .expect("Could not display dialog box");
@@ -59,7 +88,7 @@
 //! ```no_run
 //! use dialog::DialogBox;
 //!
-//! let name = dialog::Input::new("Please enter your name")
+//! let (_, name) = dialog::Input::new("Please enter your name")
 //!     .show()
 //!     .expect("Could not display dialog box");
 //! match name {
@@ -68,17 +97,38 @@
 //! };
 //! ```
 //!
+//! [`Backend::capabilities`]: backends/trait.Backend.html#method.capabilities
+//! [`Broker`]: backends/struct.Broker.html
+//! [`BrokerServer`]: backends/struct.BrokerServer.html
+//! [`Checklist`]: struct.Checklist.html
 //! [`Dialog`]: backends/struct.Dialog.html
 //! [`FileSelection`]: struct.FileSelection.html
+//! [`Fallback`]: backends/struct.Fallback.html
+//! [`Frontend`]: backends/struct.Frontend.html
+//! [`Fzf`]: backends/struct.Fzf.html
+//! [`Gum`]: backends/struct.Gum.html
 //! [`Input`]: struct.Input.html
 //! [`Message`]: struct.Message.html
+//! [`Mock`]: backends/struct.Mock.html
 //! [`Password`]: struct.Password.html
 //! [`Question`]: struct.Question.html
+//! [`Preseed`]: backends/struct.Preseed.html
+//! [`Record`]: backends/struct.Record.html
+//! [`Replay`]: backends/struct.Replay.html
+//! [`Rofi`]: backends/struct.Rofi.html
+//! [`Stdio`]: backends/struct.Stdio.html
+//! [`Web`]: backends/struct.Web.html
 //! [`default_backend`]: fn.default_backend.html
 //! [`show`]: trait.DialogBox.html#method.show
 //! [`show_with`]: trait.DialogBox.html#method.show_with
+//! [`spawn_with`]: trait.DialogBox.html#method.spawn_with
+//! [`Handle`]: struct.Handle.html
 
+#[cfg(feature = "tokio")]
+mod asynchronous;
 mod error;
+mod handle;
+mod request;
 
 /// Backends that display dialog boxes.
 ///
@@ -91,10 +141,17 @@ pub mod backends;
 
 use std::{
     env,
+    ffi::OsString,
     path::{Path, PathBuf},
 };
 
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tokio")]
+pub use crate::asynchronous::AsyncDialogBox;
 pub use crate::error::{Error, Result};
+pub use crate::handle::Handle;
+pub use crate::request::{BoxKind, Request, Response};
 
 /// A dialog box that can be shown using a backend.
 ///
@@ -105,15 +162,31 @@ pub trait DialogBox {
 
     /// Shows this dialog box using the default backend and returns the output.
     ///
-    /// `box.show()` is a shorthand for `box.show_with(default_backend())`.
+    /// `box.show()` is a shorthand for `box.show_with(default_backend()?)`.
     fn show(&self) -> Result<Self::Output> {
-        self.show_with(default_backend())
+        self.show_with(default_backend()?)
     }
 
     /// Shows this dialog box using the given backend and returns the output.
     fn show_with<B>(&self, backend: impl AsRef<B>) -> Result<Self::Output>
     where
         B: backends::Backend + ?Sized;
+
+    /// Starts displaying this dialog box using the given backend and returns a handle without
+    /// waiting for the answer.
+    ///
+    /// The returned [`Handle`][] can be used to wait for the output or to close the dialog box.
+    /// If the backend does not support this, an [`Error::Unsupported`][] error is returned.  The
+    /// default implementation always returns this error.
+    ///
+    /// [`Handle`]: struct.Handle.html
+    /// [`Error::Unsupported`]: enum.Error.html#variant.Unsupported
+    fn spawn_with<B>(&self, _backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        Err(Error::Unsupported("this dialog box cannot be displayed in the background".to_string()))
+    }
 }
 
 /// A menu box.
@@ -125,8 +198,11 @@ pub trait DialogBox {
 /// ```no_run
 /// use dialog::DialogBox;
 ///
-/// let choice = dialog::Menu::new("Please choose one of the following items..", 
-/// 10, vec![["item1","value1"], ["item2","value2"]])
+/// let items = vec![
+///     ["item1".to_string(), "value1".to_string()],
+///     ["item2".to_string(), "value2".to_string()],
+/// ];
+/// let choice = dialog::Menu::new("Please choose one of the following items..", 10, items)
 ///     .show()
 ///     .expect("Could not display menu box");
 /// ```
@@ -134,6 +210,8 @@ pub struct Menu {
     text: String,
     menu_height: u32,
     list: Vec<String>,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl Menu {
@@ -141,10 +219,25 @@ impl Menu {
     pub fn new(text: impl Into<String>, menu_height: u32, list: Vec<[String; 2]>) -> Menu {
         Menu {
             text: text.into(),
-            menu_height: menu_height,
+            menu_height,
             list: list.into_iter().flatten().collect(),
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Menu {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Menu {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for Menu {
@@ -156,9 +249,89 @@ impl DialogBox for Menu {
     {
         backend.as_ref().show_menu(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::choices_output))
+    }
 }
 
+/// A checklist box.
+///
+/// This dialog box displays a list of items that can be toggled on and off.  It returns the tags
+/// of all selected items.
+///
+/// # Example
+///
+/// ```no_run
+/// use dialog::DialogBox;
+///
+/// let items = vec![
+///     ("ssh".to_string(), "OpenSSH server".to_string(), true),
+///     ("ftp".to_string(), "FTP server".to_string(), false),
+/// ];
+/// let (choice, tags) = dialog::Checklist::new("Please select the services to enable", 10, items)
+///     .show()
+///     .expect("Could not display checklist box");
+/// ```
+pub struct Checklist {
+    text: String,
+    list_height: u32,
+    list: Vec<(String, String, bool)>,
+    id: Option<String>,
+    timeout: Option<u32>,
+}
+
+impl Checklist {
+    /// Creates a new checklist with the given text and items.
+    ///
+    /// Each item consists of a tag, a description and its initial status.
+    pub fn new(text: impl Into<String>, list_height: u32, list: Vec<(String, String, bool)>)
+        -> Checklist {
+        Checklist {
+            text: text.into(),
+            list_height,
+            list,
+            id: None,
+            timeout: None,
+        }
+    }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Checklist {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Checklist {
+        self.timeout = Some(seconds);
+        self
+    }
+}
+
+impl DialogBox for Checklist {
+    type Output = (Choice, Option<Vec<String>>);
+
+    fn show_with<B>(&self, backend: impl AsRef<B>) -> Result<Self::Output>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        backend.as_ref().show_checklist(self)
+    }
 
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::checklist_output))
+    }
+}
 
 /// A message box.
 ///
@@ -176,6 +349,8 @@ impl DialogBox for Menu {
 /// ```
 pub struct Message {
     text: String,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl Message {
@@ -183,8 +358,29 @@ impl Message {
     pub fn new(text: impl Into<String>) -> Message {
         Message {
             text: text.into(),
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box.
+    ///
+    /// The id is a stable name for the dialog box.  It is passed to the backend as part of the
+    /// [`Request`](enum.Request.html), so that the [`Preseed`](backends/struct.Preseed.html)
+    /// backend can look up the answer and the [`Replay`](backends/struct.Replay.html) backend
+    /// can match the recorded dialog box even if its text has changed.  This method returns a
+    /// reference to `self` to enable chaining.
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Message {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Message {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for Message {
@@ -196,6 +392,14 @@ impl DialogBox for Message {
     {
         backend.as_ref().show_message(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::unit_output))
+    }
 }
 
 /// A dialog box with a text input field.
@@ -208,7 +412,7 @@ impl DialogBox for Message {
 /// ```no_run
 /// use dialog::DialogBox;
 ///
-/// let name = dialog::Input::new("Please enter your name")
+/// let (_, name) = dialog::Input::new("Please enter your name")
 ///     .show()
 ///     .expect("Could not display dialog box");
 /// match name {
@@ -219,6 +423,8 @@ impl DialogBox for Message {
 pub struct Input {
     text: String,
     default: Option<String>,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl Input {
@@ -227,6 +433,8 @@ impl Input {
         Input {
             text: text.into(),
             default: None,
+            id: None,
+            timeout: None,
         }
     }
 
@@ -237,6 +445,19 @@ impl Input {
         self.default = Some(default.into());
         self
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Input {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Input {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for Input {
@@ -248,6 +469,14 @@ impl DialogBox for Input {
     {
         backend.as_ref().show_input(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::choices_output))
+    }
 }
 
 /// A dialog box with a password input field.
@@ -260,7 +489,7 @@ impl DialogBox for Input {
 /// ```no_run
 /// use dialog::DialogBox;
 ///
-/// let password = dialog::Password::new("Please enter a new password")
+/// let (_, password) = dialog::Password::new("Please enter a new password")
 ///     .show()
 ///     .expect("Could not display dialog box");
 /// match password {
@@ -270,6 +499,8 @@ impl DialogBox for Input {
 /// ```
 pub struct Password {
     text: String,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl Password {
@@ -277,8 +508,23 @@ impl Password {
     pub fn new(text: impl Into<String>) -> Password {
         Password {
             text: text.into(),
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Password {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Password {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for Password {
@@ -290,10 +536,19 @@ impl DialogBox for Password {
     {
         backend.as_ref().show_password(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::choices_output))
+    }
 }
 
 /// A user choise in a dialog box.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Choice {
     /// The ok/yes button.
     Yes,
@@ -307,6 +562,8 @@ pub enum Choice {
     Help, 
     /// The escape button.
     Escape,
+    /// The timeout expired before the user answered the dialog box.
+    Timeout,
 }
 
 /// A question dialog box.
@@ -326,6 +583,8 @@ pub enum Choice {
 /// ```
 pub struct Question {
     text: String,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl Question {
@@ -333,8 +592,23 @@ impl Question {
     pub fn new(text: impl Into<String>) -> Question {
         Question {
             text: text.into(),
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Question {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Question {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for Question {
@@ -346,10 +620,19 @@ impl DialogBox for Question {
     {
         backend.as_ref().show_question(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::choice_output))
+    }
 }
 
 /// The type of a file selection dialog.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FileSelectionMode {
     /// An Open File dialog, meaning that the user can only select an existing file.
     Open,
@@ -366,6 +649,10 @@ pub enum FileSelectionMode {
 /// select a mode using the [`FileSelectionMode`][] enum, though the backend might ignore the mode
 /// and just display a simple file dialog.  Per default, the mode is set to `Open`.
 ///
+/// The output is the path selected by the user.  File names that are not valid UTF-8 are
+/// preserved if the backend supports it; backends that exchange the answer as text, for example
+/// [`Response`](struct.Response.html), convert them lossily.
+///
 /// # Example
 ///
 /// ```no_run
@@ -379,12 +666,13 @@ pub enum FileSelectionMode {
 /// ```
 ///
 /// [`FileSelectionMode`]: enum.FileSelectionMode.html
-
 #[allow(dead_code)]
 pub struct FileSelection {
     text: String,
     path: Option<PathBuf>,
     mode: FileSelectionMode,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl FileSelection {
@@ -394,6 +682,8 @@ impl FileSelection {
             text: text.into(),
             path: dirs::home_dir(),
             mode: FileSelectionMode::Open,
+            id: None,
+            timeout: None,
         }
     }
 
@@ -407,15 +697,11 @@ impl FileSelection {
 
     /// Gets the path of this file chooser dialog box.
     ///
-    /// This method returns the validated directory as a `String`.
-    pub fn path_to_string(&self) -> Option<String> {
-        match self.path {
-            Some(ref path) if path.is_dir() => {
-                // The backends expect a trailing / after the directory
-                path.to_str().map(|s| s.to_string() + "/")
-            }
-            _ => None,
-        }
+    /// This method returns the validated directory as a `String` with a trailing slash.  If the
+    /// path is not set, is not a directory or is not valid UTF-8, an
+    /// [`Error::InvalidConfig`](enum.Error.html#variant.InvalidConfig) error is returned.
+    pub fn path_to_string(&self) -> Result<String> {
+        dir_to_string(&self.path)
     }
 
     /// Sets the operation mode of the file chooser.
@@ -425,10 +711,23 @@ impl FileSelection {
         self.mode = mode;
         self
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut FileSelection {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut FileSelection {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for FileSelection {
-    type Output = (Choice, Option<String>);
+    type Output = (Choice, Option<PathBuf>);
 
     fn show_with<B>(&self, backend: impl AsRef<B>) -> Result<Self::Output>
     where
@@ -436,6 +735,34 @@ impl DialogBox for FileSelection {
     {
         backend.as_ref().show_file_selection(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::with_raw_output(running, request::raw_file_selection_output))
+    }
+}
+
+// Validates the directory of a file selection and converts it to a string with a trailing slash,
+// as expected by the backends.
+pub(crate) fn dir_to_string(path: &Option<PathBuf>) -> Result<String> {
+    dir_to_os_string(path)?.into_string().map_err(|path| {
+        Error::InvalidConfig(format!("{} is not valid UTF-8", Path::new(&path).display()))
+    })
+}
+
+// Like dir_to_string, but also accepts directories that are not valid UTF-8.
+pub(crate) fn dir_to_os_string(path: &Option<PathBuf>) -> Result<OsString> {
+    let path = path.as_ref()
+        .ok_or_else(|| Error::InvalidConfig("no path set".to_string()))?;
+    if !path.is_dir() {
+        return Err(Error::InvalidConfig(format!("{} is not a directory", path.display())));
+    }
+    let mut path = path.clone().into_os_string();
+    path.push("/");
+    Ok(path)
 }
 
 /// Creates a new instance of the default backend.
@@ -443,18 +770,41 @@ impl DialogBox for FileSelection {
 /// The following steps are performed to determine the default backend:
 /// - If the `DIALOG` environment variable is set to a valid backend name, this backend is used.
 ///   A valid backend name is the name of a struct in the `backends` module implementing the
-///   `Backend` trait in any case.
-/// - If the [`Dialog`][] backend is available, it is used.
+///   `Backend` trait in any case, `dmenu` for the `dmenu` variant of the [`Rofi`][] backend, or
+///   `whiptail` for the `whiptail` variant of the [`Dialog`][] backend.
+///   If it is set to a colon-separated list of backend names, for example `fzf:dialog:stdio`, a
+///   [`Fallback`][] backend trying these backends in order is used.  Backends that cannot be
+///   used in the current environment are skipped.  If the variable contains an unknown name, an
+///   [`Error::InvalidConfig`][] error is returned, and if none of the backends can be used, an
+///   [`Error::NotFound`][] error naming them is returned.
+/// - Otherwise, if the process has a terminal, the [`Dialog`][] backend is used if `dialog` or
+///   `whiptail` is available, falling back to the [`Stdio`][] backend for dialog boxes that it
+///   does not support.  Without a terminal, the first backend that can be used of [`Rofi`][]
+///   (with `rofi` or `dmenu`) and [`Stdio`][] is used in the same way.
+///
+/// If a backend selected by `DIALOG` cannot be created, for example because the web server
+/// cannot be started, the error is returned.  If no backend can be used at all, an
+/// [`Error::NotFound`][] error is returned.
+///
+/// Terminal backends such as [`Dialog`][] can only be used if the process has a controlling
+/// terminal and `TERM` is set and not `dumb`.  [`Rofi`][] requires `DISPLAY` or
+/// `WAYLAND_DISPLAY`.  All backends that use an external program require the program to be
+/// found in `PATH`.
 ///
 /// [`Dialog`]: backends/struct.Dialog.html
-pub fn default_backend() -> Box<dyn backends::Backend> {
+/// [`Error::InvalidConfig`]: enum.Error.html#variant.InvalidConfig
+/// [`Error::NotFound`]: enum.Error.html#variant.NotFound
+/// [`Fallback`]: backends/struct.Fallback.html
+/// [`Rofi`]: backends/struct.Rofi.html
+/// [`Stdio`]: backends/struct.Stdio.html
+pub fn default_backend() -> Result<Box<dyn backends::Backend>> {
     if let Ok(backend) = env::var("DIALOG") {
-        if let Some(backend) = backends::from_str(&backend) {
-            return backend;
+        if let Some(backend) = backends::from_env(&backend)? {
+            return Ok(backend);
         }
     }
 
-    Box::new(backends::Dialog::new())
+    backends::detect()
 }
 
 /// A gauge box.
@@ -464,15 +814,17 @@ pub fn default_backend() -> Box<dyn backends::Backend> {
 /// # Example
 ///
 /// ```no_run
-/// use dialog::Gauge;
+/// use dialog::DialogBox;
 ///
-/// dialog::Gauge::new("progress...")
+/// dialog::Gauge::new("progress...", 50)
 ///     .show()
 ///     .expect("Could not display dialog box");
 /// ```
 pub struct Gauge {
     text: String,
     percent: u8,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl Gauge {
@@ -480,9 +832,24 @@ impl Gauge {
     pub fn new(text: impl Into<String>, percent: u8) -> Gauge {
         Gauge {
             text: text.into(),
-            percent: percent,
+            percent,
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Gauge {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Gauge {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for Gauge {
@@ -494,6 +861,14 @@ impl DialogBox for Gauge {
     {
         backend.as_ref().show_gauge(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::unit_output))
+    }
 }
 
 /// A mixed gauge box.
@@ -503,15 +878,17 @@ impl DialogBox for Gauge {
 /// # Example
 ///
 /// ```no_run
-/// use dialog::MixedGauge;
+/// use dialog::DialogBox;
 ///
-/// dialog::MixedGauge::new("progress...")
+/// dialog::MixedGauge::new("progress...", 50)
 ///     .show()
 ///     .expect("Could not display dialog box");
 /// ```
 pub struct MixedGauge {
     text: String,
     percent: u8,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl MixedGauge {
@@ -519,9 +896,24 @@ impl MixedGauge {
     pub fn new(text: impl Into<String>, percent: u8) -> MixedGauge {
         MixedGauge {
             text: text.into(),
-            percent: percent,
+            percent,
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut MixedGauge {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut MixedGauge {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for MixedGauge {
@@ -533,39 +925,68 @@ impl DialogBox for MixedGauge {
     {
         backend.as_ref().show_mixed_gauge(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::unit_output))
+    }
 }
 
+/// A field of a form box.
+///
+/// The elements are the label, its row and column, the initial item value, its row and column,
+/// the displayed field length and the maximum input length.
+pub type FormField = (String, u8, u8, String, u8, u8, u8, u8);
+
 /// A form box.
 ///
-/// A form box displays a progress bar.  
+/// A form box displays labels and text fields to be filled out.
 ///
 /// # Example
 ///
 /// ```no_run
-/// use dialog::Form;
+/// use dialog::DialogBox;
 ///
-/// dialog::Form::new("progress...")
+/// let fields = vec![("Name:".to_string(), 1, 1, "".to_string(), 1, 10, 20, 0)];
+/// dialog::Form::new("Please enter the information", 5, fields)
 ///     .show()
 ///     .expect("Could not display dialog box");
 /// ```
 pub struct Form {
     text: String,
     form_height: u32,
-    list: Vec<String>,
+    list: Vec<FormField>,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl Form {
     /// Creates a new message box with the given text.
-    pub fn new(text: impl Into<String>, form_height: u32, 
-        list: Vec<(String, u8, u8, String, u8, u8, u8, u8)>) -> Form {
+    pub fn new(text: impl Into<String>, form_height: u32, list: Vec<FormField>) -> Form {
         Form {
             text: text.into(),
-            form_height: form_height,
-            list: list.iter().map(|(x1, x2, x3, x4, x5, x6, x7, x8)| 
-            format!("{} {} {} {} {} {} {} {}", x1, x2, x3, x4, x5, x6, x7, x8))
-            .collect()
+            form_height,
+            list,
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut Form {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut Form {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for Form { 
@@ -577,6 +998,14 @@ impl DialogBox for Form {
     {
         backend.as_ref().show_form(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::choices_output))
+    }
 }
 
 /// A mixedform box.
@@ -587,30 +1016,45 @@ impl DialogBox for Form {
 /// # Example
 ///
 /// ```no_run
-/// use dialog::MixedForm;
+/// use dialog::DialogBox;
 ///
-/// dialog::MixedForm::new("Please enter the information")
+/// let fields = vec![("Name:".to_string(), 1, 1, "".to_string(), 1, 10, 20, 0)];
+/// dialog::MixedForm::new("Please enter the information", 5, fields)
 ///     .show()
 ///     .expect("Could not display dialog box");
 /// ```
 pub struct MixedForm {
     text: String,
     form_height: u32,
-    list: Vec<String>,
+    list: Vec<FormField>,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl MixedForm {
     /// Creates a new message box with the given text.
-    pub fn new(text: impl Into<String>, form_height: u32, 
-        list: Vec<(String, u8, u8, String, u8, u8, u8, u8)>) -> MixedForm {
+    pub fn new(text: impl Into<String>, form_height: u32, list: Vec<FormField>) -> MixedForm {
         MixedForm {
             text: text.into(),
-            form_height: form_height,
-            list: list.iter().map(|(x1, x2, x3, x4, x5, x6, x7, x8)| 
-            format!("{} {} {} {} {} {} {} {}", x1, x2, x3, x4, x5, x6, x7, x8))
-            .collect()
+            form_height,
+            list,
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut MixedForm {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut MixedForm {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for MixedForm {
@@ -622,6 +1066,14 @@ impl DialogBox for MixedForm {
     {
         backend.as_ref().show_mixed_form(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::choices_output))
+    }
 }
 
 /// A password form box.
@@ -631,30 +1083,45 @@ impl DialogBox for MixedForm {
 /// # Example
 ///
 /// ```no_run
-/// use dialog::PasswordForm;
+/// use dialog::DialogBox;
 ///
-/// dialog::PasswordForm::new("Please enter the information")
+/// let fields = vec![("Password:".to_string(), 1, 1, "".to_string(), 1, 10, 20, 0)];
+/// dialog::PasswordForm::new("Please enter the information", 5, fields)
 ///     .show()
 ///     .expect("Could not display dialog box");
 /// ```
 pub struct PasswordForm {
     text: String,
     form_height: u32,
-    list: Vec<String>,
+    list: Vec<FormField>,
+    id: Option<String>,
+    timeout: Option<u32>,
 }
 
 impl PasswordForm {
     /// Creates a new message box with the given text.
-    pub fn new(text: impl Into<String>, form_height: u32, 
-        list: Vec<(String, u8, u8, String, u8, u8, u8, u8)>) -> PasswordForm {
+    pub fn new(text: impl Into<String>, form_height: u32, list: Vec<FormField>) -> PasswordForm {
         PasswordForm {
             text: text.into(),
-            form_height: form_height,
-            list: list.iter().map(|(x1, x2, x3, x4, x5, x6, x7, x8)| 
-            format!("{} {} {} {} {} {} {} {}", x1, x2, x3, x4, x5, x6, x7, x8))
-            .collect()
+            form_height,
+            list,
+            id: None,
+            timeout: None,
         }
     }
+
+    /// Sets the id of this dialog box, see [`Message::id`](struct.Message.html#method.id).
+    pub fn id(&mut self, id: impl Into<String>) -> &mut PasswordForm {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the timeout of this dialog box in seconds, overriding the timeout of the backend, see
+    /// [`Dialog::set_timeout`](backends/struct.Dialog.html#method.set_timeout).
+    pub fn timeout(&mut self, seconds: u32) -> &mut PasswordForm {
+        self.timeout = Some(seconds);
+        self
+    }
 }
 
 impl DialogBox for PasswordForm {
@@ -666,6 +1133,14 @@ impl DialogBox for PasswordForm {
     {
         backend.as_ref().show_password_form(self)
     }
+
+    fn spawn_with<B>(&self, backend: impl AsRef<B>) -> Result<Handle<Self::Output>>
+    where
+        B: backends::Backend + ?Sized,
+    {
+        let running = backend.as_ref().spawn(self.into())?;
+        Ok(Handle::new(running, request::choices_output))
+    }
 }
 
 
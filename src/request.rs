@@ -0,0 +1,568 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backends::Backend;
+use crate::{
+    Checklist, Choice,
+    FileSelection, FileSelectionMode, Form,
+    FormField, Gauge, Menu, MixedForm,
+    MixedGauge, Input,
+    Message, Password,
+    PasswordForm,
+    Question, Result
+};
+
+/// The type of a dialog box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoxKind {
+    /// A [`Checklist`](struct.Checklist.html).
+    Checklist,
+    /// A [`FileSelection`](struct.FileSelection.html).
+    FileSelection,
+    /// A [`Form`](struct.Form.html).
+    Form,
+    /// A [`Gauge`](struct.Gauge.html).
+    Gauge,
+    /// An [`Input`](struct.Input.html).
+    Input,
+    /// A [`Menu`](struct.Menu.html).
+    Menu,
+    /// A [`Message`](struct.Message.html).
+    Message,
+    /// A [`MixedForm`](struct.MixedForm.html).
+    MixedForm,
+    /// A [`MixedGauge`](struct.MixedGauge.html).
+    MixedGauge,
+    /// A [`Password`](struct.Password.html).
+    Password,
+    /// A [`PasswordForm`](struct.PasswordForm.html).
+    PasswordForm,
+    /// A [`Question`](struct.Question.html).
+    Question,
+}
+
+/// A description of a dialog box that was passed to a backend.
+///
+/// Each variant contains the text and the options of the corresponding dialog box type.  Requests
+/// can be serialized, for example to JSON, with the dialog box type stored in the `type` field.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// A checklist with the list height and the tag, description and status of each item.
+    Checklist {
+        /// The text of the dialog box.
+        text: String,
+        /// The height of the list.
+        list_height: u32,
+        /// The items of the list.
+        list: Vec<(String, String, bool)>,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A file selection with the initial directory and the selection mode.
+    FileSelection {
+        /// The text of the dialog box.
+        text: String,
+        /// The initial directory.
+        path: Option<PathBuf>,
+        /// The selection mode.
+        mode: FileSelectionMode,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A form with the form height and the fields.
+    Form {
+        /// The text of the dialog box.
+        text: String,
+        /// The height of the form.
+        form_height: u32,
+        /// The fields of the form.
+        list: Vec<FormField>,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A gauge with the percentage.
+    Gauge {
+        /// The text of the dialog box.
+        text: String,
+        /// The percentage.
+        percent: u8,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// An input with the default value.
+    Input {
+        /// The text of the dialog box.
+        text: String,
+        /// The default value.
+        default: Option<String>,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A menu with the menu height and the tag and description of each item.
+    Menu {
+        /// The text of the dialog box.
+        text: String,
+        /// The height of the menu.
+        menu_height: u32,
+        /// The items of the menu.
+        list: Vec<[String; 2]>,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A message.
+    Message {
+        /// The text of the dialog box.
+        text: String,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A mixed form with the form height and the fields.
+    MixedForm {
+        /// The text of the dialog box.
+        text: String,
+        /// The height of the form.
+        form_height: u32,
+        /// The fields of the form.
+        list: Vec<FormField>,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A mixed gauge with the percentage.
+    MixedGauge {
+        /// The text of the dialog box.
+        text: String,
+        /// The percentage.
+        percent: u8,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A password input.
+    Password {
+        /// The text of the dialog box.
+        text: String,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A password form with the form height and the fields.
+    PasswordForm {
+        /// The text of the dialog box.
+        text: String,
+        /// The height of the form.
+        form_height: u32,
+        /// The fields of the form.
+        list: Vec<FormField>,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+    /// A question.
+    Question {
+        /// The text of the dialog box.
+        text: String,
+        /// The identifier of the dialog box.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// The timeout in seconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u32>,
+    },
+}
+
+impl Request {
+    /// Returns the type of the dialog box.
+    pub fn kind(&self) -> BoxKind {
+        match *self {
+            Request::Checklist { .. } => BoxKind::Checklist,
+            Request::FileSelection { .. } => BoxKind::FileSelection,
+            Request::Form { .. } => BoxKind::Form,
+            Request::Gauge { .. } => BoxKind::Gauge,
+            Request::Input { .. } => BoxKind::Input,
+            Request::Menu { .. } => BoxKind::Menu,
+            Request::Message { .. } => BoxKind::Message,
+            Request::MixedForm { .. } => BoxKind::MixedForm,
+            Request::MixedGauge { .. } => BoxKind::MixedGauge,
+            Request::Password { .. } => BoxKind::Password,
+            Request::PasswordForm { .. } => BoxKind::PasswordForm,
+            Request::Question { .. } => BoxKind::Question,
+        }
+    }
+
+    /// Shows the described dialog box using the given backend and returns the answer.
+    ///
+    /// Messages and gauges are answered with [`Choice::Yes`][] and without a value.  For
+    /// checklists, the value contains one selected tag per line.  File names that are not valid
+    /// UTF-8 are converted lossily.
+    ///
+    /// [`Choice::Yes`]: enum.Choice.html#variant.Yes
+    pub fn show_with(&self, backend: &dyn Backend) -> Result<Response> {
+        let (choice, value) = match self.clone() {
+            Request::Checklist { text, list_height, list, id, timeout } => {
+                let checklist = Checklist { text, list_height, list, id, timeout };
+                let (choice, tags) = backend.show_checklist(&checklist)?;
+                (choice, tags.map(|tags| tags.join("\n")))
+            }
+            Request::FileSelection { text, path, mode, id, timeout } => {
+                let file_selection = FileSelection { text, path, mode, id, timeout };
+                let (choice, path) = backend.show_file_selection(&file_selection)?;
+                (choice, path.map(|path| path.to_string_lossy().into_owned()))
+            }
+            Request::Form { text, form_height, list, id, timeout } => {
+                backend.show_form(&Form { text, form_height, list, id, timeout })?
+            }
+            Request::Gauge { text, percent, id, timeout } => {
+                backend.show_gauge(&Gauge { text, percent, id, timeout })?;
+                (Choice::Yes, None)
+            }
+            Request::Input { text, default, id, timeout } => {
+                backend.show_input(&Input { text, default, id, timeout })?
+            }
+            Request::Menu { text, menu_height, list, id, timeout } => {
+                let list = list.into_iter().flatten().collect();
+                backend.show_menu(&Menu { text, menu_height, list, id, timeout })?
+            }
+            Request::Message { text, id, timeout } => {
+                backend.show_message(&Message { text, id, timeout })?;
+                (Choice::Yes, None)
+            }
+            Request::MixedForm { text, form_height, list, id, timeout } => {
+                backend.show_mixed_form(&MixedForm { text, form_height, list, id, timeout })?
+            }
+            Request::MixedGauge { text, percent, id, timeout } => {
+                backend.show_mixed_gauge(&MixedGauge { text, percent, id, timeout })?;
+                (Choice::Yes, None)
+            }
+            Request::Password { text, id, timeout } => {
+                backend.show_password(&Password { text, id, timeout })?
+            }
+            Request::PasswordForm { text, form_height, list, id, timeout } => {
+                backend.show_password_form(&PasswordForm { text, form_height, list, id, timeout })?
+            }
+            Request::Question { text, id, timeout } => {
+                (backend.show_question(&Question { text, id, timeout })?, None)
+            }
+        };
+        Ok(Response::new(choice, value))
+    }
+
+    /// Returns the identifier of the dialog box, if set.
+    pub fn id(&self) -> Option<&str> {
+        match *self {
+            Request::Checklist { ref id, .. }
+            | Request::FileSelection { ref id, .. }
+            | Request::Form { ref id, .. }
+            | Request::Gauge { ref id, .. }
+            | Request::Input { ref id, .. }
+            | Request::Menu { ref id, .. }
+            | Request::Message { ref id, .. }
+            | Request::MixedForm { ref id, .. }
+            | Request::MixedGauge { ref id, .. }
+            | Request::Password { ref id, .. }
+            | Request::PasswordForm { ref id, .. }
+            | Request::Question { ref id, .. } => id.as_deref(),
+        }
+    }
+
+    /// Returns the timeout of the dialog box in seconds, if set.
+    pub fn timeout(&self) -> Option<u32> {
+        match *self {
+            Request::Checklist { timeout, .. }
+            | Request::FileSelection { timeout, .. }
+            | Request::Form { timeout, .. }
+            | Request::Gauge { timeout, .. }
+            | Request::Input { timeout, .. }
+            | Request::Menu { timeout, .. }
+            | Request::Message { timeout, .. }
+            | Request::MixedForm { timeout, .. }
+            | Request::MixedGauge { timeout, .. }
+            | Request::Password { timeout, .. }
+            | Request::PasswordForm { timeout, .. }
+            | Request::Question { timeout, .. } => timeout,
+        }
+    }
+
+    /// Returns the text of the dialog box.
+    pub fn text(&self) -> &str {
+        match *self {
+            Request::Checklist { ref text, .. }
+            | Request::FileSelection { ref text, .. }
+            | Request::Form { ref text, .. }
+            | Request::Gauge { ref text, .. }
+            | Request::Input { ref text, .. }
+            | Request::Menu { ref text, .. }
+            | Request::Message { ref text, .. }
+            | Request::MixedForm { ref text, .. }
+            | Request::MixedGauge { ref text, .. }
+            | Request::Password { ref text, .. }
+            | Request::PasswordForm { ref text, .. }
+            | Request::Question { ref text, .. } => text,
+        }
+    }
+}
+
+impl From<&Checklist> for Request {
+    fn from(checklist: &Checklist) -> Request {
+        Request::Checklist {
+            text: checklist.text.clone(),
+            list_height: checklist.list_height,
+            list: checklist.list.clone(),
+            id: checklist.id.clone(),
+            timeout: checklist.timeout,
+        }
+    }
+}
+
+impl From<&FileSelection> for Request {
+    fn from(file_selection: &FileSelection) -> Request {
+        Request::FileSelection {
+            text: file_selection.text.clone(),
+            path: file_selection.path.clone(),
+            mode: file_selection.mode,
+            id: file_selection.id.clone(),
+            timeout: file_selection.timeout,
+        }
+    }
+}
+
+impl From<&Form> for Request {
+    fn from(form: &Form) -> Request {
+        Request::Form {
+            text: form.text.clone(),
+            form_height: form.form_height,
+            list: form.list.clone(),
+            id: form.id.clone(),
+            timeout: form.timeout,
+        }
+    }
+}
+
+impl From<&Gauge> for Request {
+    fn from(gauge: &Gauge) -> Request {
+        Request::Gauge {
+            text: gauge.text.clone(),
+            percent: gauge.percent,
+            id: gauge.id.clone(),
+            timeout: gauge.timeout,
+        }
+    }
+}
+
+impl From<&Input> for Request {
+    fn from(input: &Input) -> Request {
+        Request::Input {
+            text: input.text.clone(),
+            default: input.default.clone(),
+            id: input.id.clone(),
+            timeout: input.timeout,
+        }
+    }
+}
+
+impl From<&Menu> for Request {
+    fn from(menu: &Menu) -> Request {
+        Request::Menu {
+            text: menu.text.clone(),
+            menu_height: menu.menu_height,
+            list: menu.list.chunks(2)
+                .map(|item| [item[0].clone(), item.get(1).cloned().unwrap_or_default()])
+                .collect(),
+            id: menu.id.clone(),
+            timeout: menu.timeout,
+        }
+    }
+}
+
+impl From<&Message> for Request {
+    fn from(message: &Message) -> Request {
+        Request::Message {
+            text: message.text.clone(),
+            id: message.id.clone(),
+            timeout: message.timeout,
+        }
+    }
+}
+
+impl From<&MixedForm> for Request {
+    fn from(form: &MixedForm) -> Request {
+        Request::MixedForm {
+            text: form.text.clone(),
+            form_height: form.form_height,
+            list: form.list.clone(),
+            id: form.id.clone(),
+            timeout: form.timeout,
+        }
+    }
+}
+
+impl From<&MixedGauge> for Request {
+    fn from(gauge: &MixedGauge) -> Request {
+        Request::MixedGauge {
+            text: gauge.text.clone(),
+            percent: gauge.percent,
+            id: gauge.id.clone(),
+            timeout: gauge.timeout,
+        }
+    }
+}
+
+impl From<&Password> for Request {
+    fn from(password: &Password) -> Request {
+        Request::Password {
+            text: password.text.clone(),
+            id: password.id.clone(),
+            timeout: password.timeout,
+        }
+    }
+}
+
+impl From<&PasswordForm> for Request {
+    fn from(form: &PasswordForm) -> Request {
+        Request::PasswordForm {
+            text: form.text.clone(),
+            form_height: form.form_height,
+            list: form.list.clone(),
+            id: form.id.clone(),
+            timeout: form.timeout,
+        }
+    }
+}
+
+impl From<&Question> for Request {
+    fn from(question: &Question) -> Request {
+        Request::Question {
+            text: question.text.clone(),
+            id: question.id.clone(),
+            timeout: question.timeout,
+        }
+    }
+}
+
+/// The answer of the user to a dialog box.
+///
+/// The value is the text entered or the item selected by the user, if any.  For checklists, the
+/// value contains one selected tag per line.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    /// The button chosen by the user.
+    pub choice: Choice,
+    /// The value entered or selected by the user.
+    pub value: Option<String>,
+}
+
+impl Response {
+    /// Creates a new response with the given choice and value.
+    pub fn new(choice: Choice, value: Option<String>) -> Response {
+        Response { choice, value }
+    }
+}
+
+impl From<Choice> for Response {
+    fn from(choice: Choice) -> Response {
+        Response::new(choice, None)
+    }
+}
+
+impl From<(Choice, Option<&str>)> for Response {
+    fn from(data: (Choice, Option<&str>)) -> Response {
+        Response::new(data.0, data.1.map(ToString::to_string))
+    }
+}
+
+// The answer of a backend with the value as written by the backend, which might not be valid
+// UTF-8 for file names.
+pub(crate) struct RawResponse {
+    pub(crate) kind: BoxKind,
+    pub(crate) choice: Choice,
+    pub(crate) value: Option<Vec<u8>>,
+}
+
+impl RawResponse {
+    // Converts the answer into a response.  File names are converted lossily, all other values
+    // have to be valid UTF-8.
+    pub(crate) fn into_response(self) -> Result<Response> {
+        let value = match self.kind {
+            BoxKind::FileSelection => {
+                self.value.map(|path| String::from_utf8_lossy(&path).into_owned())
+            }
+            _ => self.value.map(String::from_utf8).transpose()?,
+        };
+        Ok(Response::new(self.choice, value))
+    }
+}
+
+// Conversions of a response into the output of a dialog box.
+
+pub(crate) fn checklist_output(response: Response) -> (Choice, Option<Vec<String>>) {
+    let tags = response.value.map(|value| value.lines().map(ToString::to_string).collect());
+    (response.choice, tags)
+}
+
+pub(crate) fn choices_output(response: Response) -> (Choice, Option<String>) {
+    (response.choice, response.value)
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) fn file_selection_output(response: Response) -> (Choice, Option<PathBuf>) {
+    (response.choice, response.value.map(PathBuf::from))
+}
+
+pub(crate) fn raw_file_selection_output(response: RawResponse) -> (Choice, Option<PathBuf>) {
+    let path = response.value.map(|path| PathBuf::from(OsString::from_vec(path)));
+    (response.choice, path)
+}
+
+pub(crate) fn unit_output(_: Response) {}
+
+pub(crate) fn choice_output(response: Response) -> Choice {
+    response.choice
+}
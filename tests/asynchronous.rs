@@ -0,0 +1,60 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Tests for the async `dialog` backend using a shell script that stands in for `dialog`.
+
+#![cfg(feature = "tokio")]
+
+mod common;
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use dialog::{AsyncDialogBox, Choice, FileSelection, Input, Question};
+
+use common::stub;
+
+#[tokio::test]
+async fn answer_is_read_from_output_fd() {
+    let backend = stub("printf 'Jürgen M' >&3");
+    let answer = Input::new("Please enter your name").show_with_async(&backend).await;
+    assert_eq!(answer.unwrap(), (Choice::Yes, Some("Jürgen M".to_string())));
+}
+
+#[tokio::test]
+async fn non_utf8_file_names_are_preserved() {
+    let backend = stub("printf '/tmp/caf\\351/' >&3");
+    let answer = FileSelection::new("Choose").path(env::temp_dir()).show_with_async(&backend).await;
+    let path = PathBuf::from(OsString::from_vec(b"/tmp/caf\xe9/".to_vec()));
+    assert_eq!(answer.unwrap(), (Choice::Yes, Some(path)));
+}
+
+#[tokio::test]
+async fn exit_status_is_mapped_to_choice() {
+    let backend = stub("exit 1");
+    let choice = Question::new("Continue?").show_with_async(&backend).await;
+    assert_eq!(choice.unwrap(), Choice::No);
+}
+
+#[tokio::test]
+async fn dropped_future_terminates_dialog() {
+    let marker = env::temp_dir().join(format!("dialog-async-test-{}", process::id()));
+    let _ = fs::remove_file(&marker);
+    let script = format!(
+        "trap 'echo terminated > {}; kill $!; exit 1' TERM; sleep 60 & wait",
+        marker.display(),
+    );
+    let backend = stub(&script);
+    let future = Question::new("Continue?").show_with_async(&backend);
+    let result = tokio::time::timeout(Duration::from_millis(200), future).await;
+    assert!(result.is_err());
+    // Dropping the future waits until the script has handled the SIGTERM.
+    let text = fs::read_to_string(&marker).expect("dialog was not terminated with SIGTERM");
+    assert_eq!(text, "terminated\n");
+    fs::remove_file(&marker).unwrap();
+}
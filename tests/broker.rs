@@ -0,0 +1,111 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Tests for the `broker` backend and the broker server.
+
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::PathBuf;
+use std::thread;
+
+use dialog::backends::{Broker, BrokerServer, Mock};
+use dialog::{Choice, DialogBox, Question};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dialog-broker-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir(&dir).expect("Could not create temporary directory");
+    dir
+}
+
+#[test]
+fn broker_forwards_dialog_boxes() {
+    let dir = temp_dir("forward");
+    let path = dir.join("broker.sock");
+    let server = BrokerServer::bind(&path).expect("Could not start the broker");
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let mock = Mock::new();
+    mock.push_response(Choice::No);
+    thread::scope(|scope| {
+        let serve = scope.spawn(|| server.serve_one(&mock));
+        let choice = Question::new("Continue?").show_with(Broker::new(&path));
+        assert_eq!(choice.unwrap(), Choice::No);
+        serve.join().unwrap().expect("Could not serve dialog box");
+    });
+    assert_eq!(mock.shown()[0].text(), "Continue?");
+
+    drop(server);
+    assert!(!path.exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bind_does_not_replace_other_files() {
+    let dir = temp_dir("file");
+    let path = dir.join("broker.sock");
+    fs::write(&path, "data").unwrap();
+    assert!(BrokerServer::bind(&path).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "data");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn drop_does_not_remove_replaced_socket() {
+    let dir = temp_dir("replaced");
+    let path = dir.join("broker.sock");
+    let server = BrokerServer::bind(&path).expect("Could not start the broker");
+    fs::remove_file(&path).unwrap();
+    fs::write(&path, "data").unwrap();
+    drop(server);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "data");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn stale_socket_is_replaced() {
+    let dir = temp_dir("stale");
+    let path = dir.join("broker.sock");
+    // Leak the first server so that its socket file is not removed.
+    std::mem::forget(BrokerServer::bind(&path).expect("Could not start the broker"));
+    assert!(BrokerServer::bind(&path).is_err());
+
+    fs::remove_file(&path).unwrap();
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(fs::symlink_metadata(&path).unwrap().file_type().is_socket());
+    let server = BrokerServer::bind(&path).expect("Could not replace stale socket");
+    drop(server);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn allowed_users_and_group_set_socket_permissions() {
+    let dir = temp_dir("allowed");
+    let path = dir.join("broker.sock");
+    let gid = fs::metadata(&dir).unwrap().gid();
+
+    let server = BrokerServer::bind(&path)
+        .and_then(|server| server.set_allowed_group(gid))
+        .expect("Could not start the broker");
+    let metadata = fs::metadata(&path).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o660);
+    assert_eq!(metadata.gid(), gid);
+
+    let server = server.set_allowed_users([65534]).expect("Could not allow users");
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o666);
+
+    let mock = Mock::new();
+    mock.push_response(Choice::Yes);
+    thread::scope(|scope| {
+        let serve = scope.spawn(|| server.serve_one(&mock));
+        let backend = Broker::new(&path).allow_user(65534);
+        assert_eq!(Question::new("Continue?").show_with(backend).unwrap(), Choice::Yes);
+        serve.join().unwrap().expect("Could not serve dialog box");
+    });
+
+    drop(server);
+    let leftovers: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert!(leftovers.is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
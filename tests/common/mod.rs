@@ -0,0 +1,53 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Helpers for tests that run a shell script instead of `dialog`.
+
+#![allow(dead_code)]
+
+use std::ffi::CStr;
+use std::fs;
+use std::sync::Once;
+
+use dialog::backends::Dialog;
+
+// The version that the stub reports for `dialog --print-version`.
+pub const VERSION: &str = "1.3-20201126";
+
+/// Returns a backend that runs the given shell script instead of `dialog`.
+///
+/// The `dialog` arguments are passed to the script as positional parameters, starting with the
+/// executable name in `$1`.  The process is given a controlling terminal first because the
+/// backend refuses to start `dialog` without one.
+pub fn stub(script: &str) -> Dialog {
+    terminal();
+    let script = format!(
+        "if [ \"$2\" = --print-version ]; then echo 'Version: {}'; exit 0; fi\n{}",
+        VERSION, script,
+    );
+    Dialog::new().set_wrapper(["sh".to_string(), "-c".to_string(), script, "sh".to_string()])
+}
+
+/// Makes sure that the test process has a controlling terminal.
+///
+/// If `/dev/tty` cannot be opened, the process is moved to a new session with a pseudo terminal
+/// as its controlling terminal.  The pseudo terminal stays open until the process exits.
+pub fn terminal() {
+    static TERMINAL: Once = Once::new();
+    TERMINAL.call_once(|| {
+        if fs::OpenOptions::new().read(true).write(true).open("/dev/tty").is_ok() {
+            return;
+        }
+        unsafe {
+            let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(master >= 0, "Could not open pseudo terminal");
+            assert_eq!(libc::grantpt(master), 0);
+            assert_eq!(libc::unlockpt(master), 0);
+            let name = CStr::from_ptr(libc::ptsname(master)).to_owned();
+            assert_ne!(libc::setsid(), -1, "Could not create session");
+            let slave = libc::open(name.as_ptr(), libc::O_RDWR);
+            assert!(slave >= 0, "Could not open pseudo terminal");
+            assert_ne!(libc::ioctl(slave, libc::TIOCSCTTY, 0), -1);
+        }
+    });
+}
@@ -0,0 +1,181 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Tests for the `dialog` backend using a shell script that stands in for `dialog`.
+
+mod common;
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::PermissionsExt;
+use std::process;
+
+use dialog::backends::Dialog;
+use dialog::{Choice, DialogBox, Error, FileSelection, Input, Message, PasswordForm, Question};
+
+use common::stub;
+
+// Exits with dialog's timeout status if the timeout option is set to 60 seconds.
+const TIMEOUT: &str = "
+while [ $# -gt 0 ]; do [ \"$1\" = --timeout ] && [ \"$2\" = 60 ] && exit 5; shift; done
+exit 0
+";
+
+#[test]
+fn timeout_is_reported() {
+    let backend = stub(TIMEOUT).set_timeout(60);
+    let answer = Input::new("Please enter your name").show_with(&backend).unwrap();
+    assert_eq!(answer, (Choice::Timeout, None));
+    let answer = Input::new("Please enter your name").timeout(30).show_with(&backend).unwrap();
+    assert_eq!(answer, (Choice::Yes, Some(String::new())));
+    // Messages are closed by the timeout without an error.
+    Message::new("Hello").show_with(&backend).unwrap();
+}
+
+#[test]
+fn timeout_answer_is_returned_with_initial_value() {
+    let backend = stub("exit 5").set_timeout(60).set_timeout_answer(Choice::Yes);
+    let answer = Input::new("Host").default("localhost").show_with(&backend).unwrap();
+    assert_eq!(answer, (Choice::Yes, Some("localhost".to_string())));
+
+    let dir = env::temp_dir();
+    let answer = FileSelection::new("Choose").path(&dir).show_with(&backend).unwrap();
+    assert_eq!(answer, (Choice::Yes, Some(dir.join(""))));
+}
+
+// Writes the arguments and the contents of the options file to the output file descriptor.
+const ARGS: &str = "
+printf '%s\\n' \"$@\" >&3
+echo ---- >&3
+cat /dev/fd/4 >&3
+";
+
+#[test]
+fn secrets_are_passed_in_file() {
+    let backend = stub(ARGS);
+    let (_, output) = Input::new("Password").default("s3cret").show_with(&backend).unwrap();
+    let output = output.unwrap();
+    let (args, file) = output.split_once("----\n").unwrap();
+    assert_eq!(args, "dialog\n--output-fd\n3\n--file\n/dev/fd/4\n");
+    assert_eq!(file, "\"--inputbox\"\n\"Password\"\n\"0\"\n\"0\"\n\"s3cret\"\n");
+
+    let list = vec![("PIN".to_string(), 1, 1, "1234".to_string(), 1, 10, 10, 0)];
+    let (_, output) = PasswordForm::new("Login", 1, list).show_with(&backend).unwrap();
+    let output = output.unwrap();
+    let (args, file) = output.split_once("----\n").unwrap();
+    assert_eq!(args, "dialog\n--output-fd\n3\n--file\n/dev/fd/4\n");
+    assert!(file.contains("\"1234\""));
+}
+
+#[test]
+fn unread_options_file_is_reported() {
+    // The options do not fit into the pipe, so writing them fails if dialog does not read them.
+    let backend = stub("exit 0");
+    let default = "x".repeat(1024 * 1024);
+    let result = Input::new("Name").default(default).show_with(&backend);
+    match result {
+        Err(Error::IoError(err)) => assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn non_utf8_start_dir_is_passed() {
+    let mut name = format!("dialog-test-{}-caf", process::id()).into_bytes();
+    name.push(0xe9);
+    let dir = env::temp_dir().join(OsString::from_vec(name));
+    fs::create_dir_all(&dir).unwrap();
+
+    // Returns the argument of --fselect, which follows the executable and --output-fd 3.
+    let backend = stub("printf '%s' \"$5\" >&3");
+    let result = FileSelection::new("Choose").path(&dir).show_with(&backend);
+    // Shell scripts are text, so they cannot contain the directory.
+    let mut script = backend.script();
+    let pushed = script.push("file", &*FileSelection::new("Choose").path(&dir));
+    fs::remove_dir(&dir).unwrap();
+    assert_eq!(result.unwrap(), (Choice::Yes, Some(dir.join(""))));
+    assert!(matches!(pushed, Err(Error::InvalidConfig(_))));
+}
+
+#[test]
+fn flatpak_spawn_forwards_fds_and_env() {
+    // A stub for flatpak-spawn that writes its arguments to the output file descriptor.
+    let dir = env::temp_dir().join(format!("dialog-test-{}-flatpak", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let wrapper = dir.join("flatpak-spawn");
+    fs::write(&wrapper, "#!/bin/sh\nprintf '%s\\n' \"$@\" >&3\n").unwrap();
+    fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+
+    common::terminal();
+    let backend = Dialog::new()
+        .set_wrapper([wrapper.to_str().unwrap(), "--host"])
+        .set_env("LANG", "C");
+    let result = Input::new("Name").default("s3cret").show_with(&backend);
+    fs::remove_dir_all(&dir).unwrap();
+    let expected = "--host\n--forward-fd=3\n--forward-fd=4\n--env=LANG=C\ndialog\n\
+        --output-fd\n3\n--file\n/dev/fd/4\n";
+    assert_eq!(result.unwrap(), (Choice::Yes, Some(expected.to_string())));
+}
+
+#[test]
+fn wrappers_closing_fds_are_rejected() {
+    let backend = Dialog::new().set_wrapper(["sudo", "-u", "nobody"]);
+    let result = backend.command_line(&Message::new("Hello"));
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+    let result = Message::new("Hello").show_with(&backend);
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+}
+
+#[test]
+fn relative_start_dir_is_resolved_in_current_dir() {
+    let dir = env::temp_dir().join(format!("dialog-test-{}-cwd", process::id()));
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    let command = Dialog::new()
+        .set_current_dir(&dir)
+        .command_line(&*FileSelection::new("Choose").path("sub"));
+    fs::remove_dir_all(&dir).unwrap();
+    let mut expected = dir.join("sub").into_os_string();
+    expected.push("/");
+    assert_eq!(command.unwrap().args()[3], expected);
+}
+
+#[test]
+fn secrets_are_not_passed_to_old_dialog() {
+    common::terminal();
+    let script = "if [ \"$2\" = --print-version ]; then echo 'Version: 1.1-20080101'; exit 0; fi
+        printf '%s ' \"$@\" >&3";
+    let backend = Dialog::new().set_wrapper(["sh", "-c", script, "sh"]);
+    let result = Input::new("Password").default("s3cret").show_with(&backend);
+    assert!(matches!(result, Err(Error::Unsupported(_))));
+    let (_, args) = Input::new("Name").show_with(&backend).unwrap();
+    assert_eq!(args.unwrap(), "dialog --output-fd 3 --inputbox Name 0 0 ");
+}
+
+#[test]
+fn whiptail_boxes_without_file_option_are_rejected() {
+    let whiptail = Dialog::whiptail().set_executable("/nonexistent/whiptail");
+    assert_eq!(whiptail.version().unwrap(), None);
+    let result = Input::new("Password").default("s3cret").show_with(&whiptail);
+    assert!(matches!(result, Err(Error::Unsupported(ref msg)) if msg.contains("--file")));
+    let result = FileSelection::new("File").show_with(&whiptail);
+    assert!(matches!(result, Err(Error::Unsupported(ref msg)) if msg.contains("--fselect")));
+}
+
+#[test]
+fn whiptail_exit_status_is_mapped() {
+    common::terminal();
+    let whiptail = |script: &str| Dialog::whiptail().set_wrapper(["sh", "-c", script, "sh"]);
+    let (choice, name) = Input::new("Name").show_with(whiptail("printf 'Jane' >&3")).unwrap();
+    assert_eq!((choice, name.as_deref()), (Choice::Yes, Some("Jane")));
+    let choice = Question::new("Continue?").show_with(whiptail("exit 1")).unwrap();
+    assert_eq!(choice, Choice::No);
+    let (choice, _) = Input::new("Name").show_with(whiptail("exit 255")).unwrap();
+    assert_eq!(choice, Choice::Escape);
+    let result = Input::new("Name").show_with(whiptail("echo 'Box options:' >&2; exit 255"));
+    match result {
+        Err(Error::CommandFailed { command, .. }) => assert_eq!(command, "whiptail"),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
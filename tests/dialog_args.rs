@@ -0,0 +1,161 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Regression tests for the arguments passed to `dialog`:  user-supplied text must never be read
+//! as an option, and control characters must not reach the terminal.
+
+use std::ffi::OsString;
+
+use dialog::backends::Dialog;
+use dialog::{Checklist, Form, Input, Menu, Message, Request};
+
+fn args(dialog: &Dialog, request: impl Into<Request>) -> Vec<OsString> {
+    dialog
+        .command_line(request)
+        .expect("Could not build command line")
+        .args()
+        .to_vec()
+}
+
+#[test]
+fn plain_text_is_not_escaped() {
+    let args = args(&Dialog::new(), &Message::new("Hello - world"));
+    assert_eq!(args, ["--output-fd", "3", "--msgbox", "Hello - world", "0", "0"]);
+}
+
+#[test]
+fn text_starting_with_dashes_is_escaped() {
+    let args = args(&Dialog::new(), &Message::new("--clear"));
+    assert_eq!(args, ["--output-fd", "3", "--msgbox", "--", "--clear", "0", "0"]);
+}
+
+#[test]
+fn double_dash_is_escaped() {
+    let args = args(&Dialog::new(), &Message::new("--"));
+    assert_eq!(args, ["--output-fd", "3", "--msgbox", "--", "--", "0", "0"]);
+}
+
+#[test]
+fn single_dash_is_not_escaped() {
+    let args = args(&Dialog::new(), &Message::new("-n"));
+    assert_eq!(args, ["--output-fd", "3", "--msgbox", "-n", "0", "0"]);
+}
+
+#[test]
+fn backend_options_are_escaped() {
+    let dialog = Dialog::new()
+        .set_backtitle("--stdout")
+        .set_title("--help")
+        .set_oklabel("--and-widget");
+    let args = args(&dialog, &Message::new("text"));
+    assert_eq!(args, [
+        "--output-fd", "3",
+        "--backtitle", "--", "--stdout",
+        "--title", "--", "--help",
+        "--ok-label", "--", "--and-widget",
+        "--msgbox", "text", "0", "0",
+    ]);
+}
+
+#[test]
+fn menu_items_are_escaped() {
+    let list = vec![
+        ["--clear".to_string(), "host".to_string()],
+        ["www".to_string(), "--print-version".to_string()],
+    ];
+    let args = args(&Dialog::new(), &Menu::new("Choose a host", 5, list));
+    assert_eq!(args, [
+        "--output-fd", "3", "--menu", "Choose a host", "0", "0", "5",
+        "--", "--clear", "host",
+        "www", "--", "--print-version",
+    ]);
+}
+
+#[test]
+fn checklist_items_are_escaped() {
+    let list = vec![("--stderr".to_string(), "--help".to_string(), true)];
+    let args = args(&Dialog::new(), &Checklist::new("Choose", 5, list));
+    assert_eq!(args, [
+        "--output-fd", "3", "--separate-output", "--checklist", "Choose", "0", "0", "5",
+        "--", "--stderr", "--", "--help", "on",
+    ]);
+}
+
+#[test]
+fn form_fields_are_escaped() {
+    let list = vec![("--label".to_string(), 1, 1, "--item".to_string(), 1, 10, 20, 0)];
+    let args = args(&Dialog::new(), &Form::new("Form", 2, list));
+    assert_eq!(args, [
+        "--output-fd", "3", "--form", "Form", "0", "0", "2",
+        "--", "--label", "1", "1", "--", "--item", "1", "10", "20", "0",
+    ]);
+}
+
+#[test]
+fn input_default_is_escaped() {
+    let args = args(&Dialog::new(), &*Input::new("Name").default("--version"));
+    assert_eq!(args, [
+        "--output-fd", "3", "--inputbox", "Name", "0", "0", "--", "--version",
+    ]);
+}
+
+#[test]
+fn control_characters_are_removed() {
+    let args = args(&Dialog::new(), &Message::new("\x1b[2Jline\r\x07\none\ttwo\x7f"));
+    assert_eq!(args, ["--output-fd", "3", "--msgbox", "[2Jline\none\ttwo", "0", "0"]);
+}
+
+#[test]
+fn control_characters_cannot_hide_dashes() {
+    let list = vec![["\x1b--clear".to_string(), "host".to_string()]];
+    let args = args(&Dialog::new(), &Menu::new("Choose", 5, list));
+    assert_eq!(args, [
+        "--output-fd", "3", "--menu", "Choose", "0", "0", "5", "--", "--clear", "host",
+    ]);
+}
+
+#[test]
+fn escaped_text_is_quoted_in_command_line() {
+    let command = Dialog::new()
+        .command_line(&Message::new("--clear me"))
+        .expect("Could not build command line");
+    assert_eq!(command.to_string(), "dialog --output-fd 3 --msgbox -- '--clear me' 0 0");
+}
+
+#[test]
+fn whiptail_options_are_translated() {
+    let whiptail = Dialog::whiptail()
+        .set_title("--help")
+        .set_oklabel("Next")
+        .set_cancellabel("Back")
+        .set_extralabel("Extra")
+        .set_helplabel("Help")
+        .set_insecure(true)
+        .set_colors(true)
+        .set_timeout(10)
+        .no_cancelbutton();
+    let command = whiptail
+        .command_line(&Input::new("--clear"))
+        .expect("Could not build command line");
+    assert_eq!(command.program(), "whiptail");
+    assert_eq!(command.args(), [
+        "--output-fd", "3",
+        "--title", "--help",
+        "--ok-button", "Next",
+        "--cancel-button", "Back",
+        "--nocancel",
+        "--inputbox", "--", "--clear", "0", "0",
+    ]);
+}
+
+#[test]
+fn whiptail_items_are_positional() {
+    let menu = Menu::new("--", 5, vec![
+        ["--a".to_string(), "first".to_string()],
+        ["b".to_string(), "--".to_string()],
+    ]);
+    let args = args(&Dialog::whiptail(), &menu);
+    assert_eq!(args, [
+        "--output-fd", "3", "--menu", "--", "--", "0", "0", "5", "--a", "first", "b", "--",
+    ]);
+}
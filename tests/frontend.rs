@@ -0,0 +1,55 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Tests for the `frontend` backend that use the `frontend` example as the frontend process.
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use dialog::backends::Frontend;
+use dialog::{Checklist, Choice, DialogBox, Input, Menu, Message, Question};
+
+// The examples are built next to the directory that contains the test executable.
+fn example() -> PathBuf {
+    let exe = std::env::current_exe().expect("Could not find test executable");
+    let dir = exe.parent().and_then(|deps| deps.parent()).expect("Unexpected target directory");
+    dir.join("examples").join("frontend")
+}
+
+#[test]
+fn reference_frontend_answers_requests() {
+    let backend = Frontend::new(example(), &[] as &[&str]).expect("Could not start the frontend");
+
+    Message::new("Hello").show_with(&backend).expect("Could not display message");
+    assert_eq!(Question::new("Continue?").show_with(&backend).unwrap(), Choice::Yes);
+
+    let input = Input::new("Name").default("Jane").show_with(&backend).unwrap();
+    assert_eq!(input, (Choice::Yes, Some("Jane".to_string())));
+
+    let items = vec![
+        ["a".to_string(), "First".to_string()],
+        ["b".to_string(), "Second".to_string()],
+    ];
+    let menu = Menu::new("Choose", 2, items).show_with(&backend).unwrap();
+    assert_eq!(menu, (Choice::Yes, Some("a".to_string())));
+
+    let items = vec![
+        ("a".to_string(), "First".to_string(), false),
+        ("b".to_string(), "Second".to_string(), true),
+        ("c".to_string(), "Third".to_string(), true),
+    ];
+    let checklist = Checklist::new("Select", 3, items).show_with(&backend).unwrap();
+    assert_eq!(checklist, (Choice::Yes, Some(vec!["b".to_string(), "c".to_string()])));
+}
+
+#[test]
+fn frontend_is_killed_if_it_does_not_exit() {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg("trap '' TERM; cat >/dev/null; exec sleep 60");
+    let backend = Frontend::spawn(command).expect("Could not start the frontend");
+
+    let start = Instant::now();
+    drop(backend);
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
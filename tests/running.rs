@@ -0,0 +1,123 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Tests for dialog boxes displayed in the background using a shell script that stands in for
+//! `dialog`.
+
+mod common;
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use dialog::backends::{Backend, Dialog};
+use dialog::{Choice, DialogBox, Error, FileSelection, Input, Question, Result};
+
+use common::stub;
+
+// Waits until the given function returns true.
+fn wait_until(f: impl Fn() -> bool) {
+    for _ in 0..500 {
+        if f() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("Timed out");
+}
+
+#[test]
+fn handle_returns_answer() {
+    let backend = stub("printf 'Jürgen M' >&3");
+    let handle = Input::new("Please enter your name").spawn_with(&backend).unwrap();
+    assert_eq!(handle.wait().unwrap(), (Choice::Yes, Some("Jürgen M".to_string())));
+}
+
+#[test]
+fn handle_preserves_non_utf8_file_names() {
+    let backend = stub("printf '/tmp/caf\\351/' >&3");
+    let handle = FileSelection::new("Choose").path(env::temp_dir()).spawn_with(&backend).unwrap();
+    let path = PathBuf::from(OsString::from_vec(b"/tmp/caf\xe9/".to_vec()));
+    assert_eq!(handle.wait().unwrap(), (Choice::Yes, Some(path)));
+}
+
+#[test]
+fn closed_dialog_box_is_cancelled() {
+    let backend = stub("exec sleep 60");
+    let handle = Question::new("Continue?").spawn_with(&backend).unwrap();
+    assert!(!handle.is_finished().unwrap());
+    handle.close().unwrap();
+    wait_until(|| handle.is_finished().unwrap());
+    // Closing the dialog box again does nothing.
+    handle.close().unwrap();
+    assert!(matches!(handle.wait(), Err(Error::Cancelled)));
+}
+
+#[test]
+fn closer_works_from_other_thread() {
+    let backend = stub("exec sleep 60");
+    let handle = Question::new("Continue?").spawn_with(&backend).unwrap();
+    let closer = handle.closer();
+    thread::spawn(move || closer.close().unwrap()).join().unwrap();
+    assert!(matches!(handle.wait(), Err(Error::Cancelled)));
+}
+
+#[test]
+fn answered_dialog_box_is_not_cancelled() {
+    let backend = stub("exit 1");
+    let handle = Question::new("Continue?").spawn_with(&backend).unwrap();
+    // The process has exited, but it has not been reaped yet.
+    wait_until(|| handle.is_finished().unwrap());
+    handle.closer().close().unwrap();
+    assert_eq!(handle.wait().unwrap(), Choice::No);
+}
+
+#[test]
+fn dropped_handle_terminates_dialog() {
+    let marker = env::temp_dir().join(format!("dialog-running-test-{}", process::id()));
+    let _ = fs::remove_file(&marker);
+    let script = format!("echo $$ > {}; exec sleep 60", marker.display());
+    let backend = stub(&script);
+    let handle = Question::new("Continue?").spawn_with(&backend).unwrap();
+    wait_until(|| fs::read_to_string(&marker).is_ok_and(|pid| pid.ends_with('\n')));
+    let pid: libc::pid_t = fs::read_to_string(&marker).unwrap().trim().parse().unwrap();
+    fs::remove_file(&marker).unwrap();
+
+    drop(handle);
+    // The process has been reaped, so it does not exist anymore.
+    assert_eq!(unsafe { libc::kill(pid, 0) }, -1);
+}
+
+#[test]
+fn running_is_used_directly() {
+    let backend = stub("printf tag >&3; exit 3");
+    let running = backend.spawn((&Input::new("Name")).into()).unwrap();
+    let response = running.wait().unwrap();
+    assert_eq!(response.choice, Choice::Extra);
+    assert_eq!(response.value.as_deref(), Some("tag"));
+}
+
+// A dialog box type defined outside of the crate that does not implement spawn_with.
+struct Custom;
+
+impl DialogBox for Custom {
+    type Output = ();
+
+    fn show_with<B>(&self, _backend: impl AsRef<B>) -> Result<()>
+    where
+        B: Backend + ?Sized,
+    {
+        Ok(())
+    }
+}
+
+#[test]
+fn spawn_with_is_unsupported_by_default() {
+    let result = Custom.spawn_with(Dialog::new());
+    assert!(matches!(result, Err(Error::Unsupported(_))));
+}
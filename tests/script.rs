@@ -0,0 +1,43 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Tests that run the scripts created by `Dialog::script` with a stub for `dialog`.
+
+mod common;
+
+use std::process;
+
+use dialog::{Gauge, Input, Message, MixedGauge, Question};
+
+// Like dialog, the stub fails if the output file descriptor is not open.  Input boxes write an
+// answer to it.
+const STUB: &str = "
+if ! { true >&3; } 2>/dev/null; then echo 'Error: Cannot open output-fd' >&2; exit 255; fi
+for arg; do [ \"$arg\" = --inputbox ] && printf 'Jürgen M' >&3; done
+exit 0
+";
+
+#[test]
+fn script_opens_output_fd_for_all_dialog_boxes() {
+    let backend = common::stub(STUB);
+    let mut script = backend.script();
+    script.push("name", &Input::new("Please enter your name")).unwrap();
+    script.push("message", &Message::new("Hello")).unwrap();
+    script.push("question", &Question::new("Continue?")).unwrap();
+    script.push("gauge", &Gauge::new("Loading", 50)).unwrap();
+    script.push("mixed", &MixedGauge::new("Loading", 50)).unwrap();
+    // The test process might have other files open as fd 3, so it is closed first.
+    let script = format!(
+        "exec 3>&-\n{}echo \"$name|$message_status|$question_status|$gauge_status|$mixed_status\"\n",
+        script,
+    );
+
+    let output = process::Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .stderr(process::Stdio::inherit())
+        .output()
+        .expect("Could not run script");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "Jürgen M|0|0|0|0\n");
+}
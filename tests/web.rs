@@ -0,0 +1,124 @@
+// Copyright (C) 2019 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: MIT
+
+//! Tests for the `web` backend that answer dialog boxes using a minimal HTTP client.
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+
+use dialog::backends::Web;
+use dialog::{Choice, DialogBox, Input, Question};
+
+// Sends an HTTP request to the given URL and returns the status code and the response body.
+fn request(url: &str, method: &str, body: &str) -> (u16, String) {
+    let rest = url.strip_prefix("http://").expect("Unexpected URL");
+    let (addr, target) = rest.split_at(rest.find('/').expect("URL without path"));
+    let mut stream = TcpStream::connect(addr).expect("Could not connect to server");
+    write!(stream, "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/x-www-form-urlencoded\r\n\
+        Content-Length: {}\r\n\r\n{}", method, target, addr, body.len(), body)
+        .expect("Could not send request");
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).expect("Could not read response");
+    let status = response.split_whitespace().nth(1).and_then(|s| s.parse().ok()).unwrap_or(0);
+    let body = response.split_once("\r\n\r\n").map(|(_, body)| body).unwrap_or_default();
+    (status, body.to_string())
+}
+
+fn with_token(url: &str, token: &str) -> String {
+    format!("{}={}", url.rsplit_once('=').expect("URL without token").0, token)
+}
+
+#[test]
+fn dialog_box_is_answered_over_http() {
+    let (sender, urls) = mpsc::channel();
+    let backend = Web::new()
+        .expect("Could not start web server")
+        .set_url_handler(move |url| sender.send(url.to_string()).unwrap());
+
+    thread::scope(|scope| {
+        let answer = scope.spawn(|| Input::new("Please enter your name").show_with(&backend));
+        let url = urls.recv().unwrap();
+
+        let (status, page) = request(&url, "GET", "");
+        assert_eq!(status, 200);
+        assert!(page.contains("Please enter your name"));
+        assert!(page.contains("name=\"seq\" value=\"1\""));
+
+        let (status, _) = request(&url, "POST", "seq=1&choice=yes&value=J%C3%BCrgen+M");
+        assert_eq!(status, 303);
+        let answer = answer.join().unwrap().expect("Could not display dialog box");
+        assert_eq!(answer, (Choice::Yes, Some("Jürgen M".to_string())));
+    });
+}
+
+#[test]
+fn token_and_sequence_number_are_checked() {
+    let (sender, urls) = mpsc::channel();
+    let backend = Web::new()
+        .expect("Could not start web server")
+        .set_url_handler(move |url| sender.send(url.to_string()).unwrap());
+
+    thread::scope(|scope| {
+        let first = scope.spawn(|| Question::new("First?").show_with(&backend));
+        let first_url = urls.recv().unwrap();
+        assert_eq!(backend.url().unwrap(), first_url);
+        assert_eq!(request(&with_token(&first_url, "0123"), "GET", "").0, 403);
+        assert_eq!(request(&with_token(&first_url, ""), "POST", "seq=1&choice=yes").0, 403);
+        request(&first_url, "POST", "seq=1&choice=no");
+        assert_eq!(first.join().unwrap().unwrap(), Choice::No);
+
+        let second = scope.spawn(|| Question::new("Second?").show_with(&backend));
+        let second_url = urls.recv().unwrap();
+        assert_eq!(first_url, second_url);
+        // a submission of the previous dialog box is ignored
+        request(&second_url, "POST", "seq=1&choice=no");
+        request(&second_url, "POST", "seq=2&choice=yes");
+        assert_eq!(second.join().unwrap().unwrap(), Choice::Yes);
+    });
+}
+
+#[test]
+fn waiting_page_reaches_next_dialog_box() {
+    let (sender, urls) = mpsc::channel();
+    let backend = Web::new()
+        .expect("Could not start web server")
+        .set_url_handler(move |url| sender.send(url.to_string()).unwrap());
+
+    thread::scope(|scope| {
+        let first = scope.spawn(|| Question::new("First?").show_with(&backend));
+        let url = urls.recv().unwrap();
+        request(&url, "POST", "seq=1&choice=yes");
+        assert_eq!(first.join().unwrap().unwrap(), Choice::Yes);
+
+        // the waiting page reloads the URL it was loaded from
+        let (status, page) = request(&url, "GET", "");
+        assert_eq!(status, 200);
+        assert!(page.contains("http-equiv=\"refresh\" content=\"1\""));
+        assert!(!page.contains("url="));
+
+        let second = scope.spawn(|| Question::new("Second?").show_with(&backend));
+        urls.recv().unwrap();
+        let (status, page) = request(&url, "GET", "");
+        assert_eq!(status, 200);
+        assert!(page.contains("Second?"));
+        request(&url, "POST", "seq=2&choice=no");
+        assert_eq!(second.join().unwrap().unwrap(), Choice::No);
+    });
+}
+
+#[test]
+fn timeout_choice_is_accepted() {
+    let (sender, urls) = mpsc::channel();
+    let backend = Web::new()
+        .expect("Could not start web server")
+        .set_url_handler(move |url| sender.send(url.to_string()).unwrap());
+
+    thread::scope(|scope| {
+        let answer = scope.spawn(|| Question::new("Continue?").show_with(&backend));
+        request(&urls.recv().unwrap(), "POST", "seq=1&choice=timeout");
+        assert_eq!(answer.join().unwrap().unwrap(), Choice::Timeout);
+    });
+}